@@ -1,9 +1,10 @@
 // examples/advanced_usage.rs
 // Run with: cargo run --example advanced_usage
 
-use anyhow;
+use futures::StreamExt;
 use rusty_sftp::{
     CancellationToken, FileTransferOperationResult, SftpClientConfig, SftpSessionManager,
+    WalkOptions,
 };
 use std::path::PathBuf;
 use std::time::Duration;
@@ -23,6 +24,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Example 4: Error handling and retry logic
     example_with_retry().await?;
 
+    // Example 5: Connection health check
+    example_connection_check().await?;
+
+    // Example 6: Directory listing
+    example_recursive_list().await?;
+
     Ok(())
 }
 
@@ -43,6 +50,7 @@ async fn example_batch_upload() -> Result<(), Box<dyn std::error::Error>> {
         .create_sftp_client(SftpClientConfig {
             io_size: 65_536,
             concurrency: 10,
+            ..SftpClientConfig::default()
         })
         .await?;
 
@@ -52,14 +60,14 @@ async fn example_batch_upload() -> Result<(), Box<dyn std::error::Error>> {
         ("/local/file3.txt", "/remote/file3.txt"),
     ];
 
-    let mut handles = vec![];
+    let mut uploads = vec![];
 
     for (local, remote) in files_to_upload {
-        let client_clone = &client;
+        let client = &client;
         let cancel_token = CancellationToken::new();
 
-        let handle = tokio::spawn(async move {
-            let result = client_clone
+        uploads.push(async move {
+            let result = client
                 .put(local.to_string(), remote.to_string(), cancel_token)
                 .await;
 
@@ -76,14 +84,10 @@ async fn example_batch_upload() -> Result<(), Box<dyn std::error::Error>> {
                 _ => {}
             }
         });
-
-        handles.push(handle);
     }
 
     // Wait for all uploads to complete
-    for handle in handles {
-        handle.await?;
-    }
+    futures::future::join_all(uploads).await;
 
     client.close().await?;
     manager.close().await?;
@@ -168,6 +172,8 @@ async fn example_multiple_clients() -> Result<(), Box<dyn std::error::Error>> {
         .create_sftp_client(SftpClientConfig {
             io_size: 131_072, // 128KB for uploads
             concurrency: 16,
+            check_disk_space: true, // fail fast when the remote disk is full
+            ..SftpClientConfig::default()
         })
         .await?;
 
@@ -175,10 +181,12 @@ async fn example_multiple_clients() -> Result<(), Box<dyn std::error::Error>> {
         .create_sftp_client(SftpClientConfig {
             io_size: 65_536, // 64KB for downloads
             concurrency: 8,
+            ..SftpClientConfig::default()
         })
         .await?;
 
     let cancel_token = CancellationToken::new();
+    let upload_cancel_token = cancel_token.clone();
 
     // Use clients concurrently
     let upload_handle = tokio::spawn(async move {
@@ -186,7 +194,7 @@ async fn example_multiple_clients() -> Result<(), Box<dyn std::error::Error>> {
             .put(
                 "/local/upload.dat".to_string(),
                 "/remote/upload.dat".to_string(),
-                cancel_token.clone(),
+                upload_cancel_token,
             )
             .await
     });
@@ -333,32 +341,27 @@ async fn example_recursive_list() -> Result<(), Box<dyn std::error::Error>> {
         .create_sftp_client(SftpClientConfig::default())
         .await?;
 
-    async fn list_recursive(
-        client: &rusty_sftp::SftpClient,
-        path: &str,
-        depth: usize,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    {
         let cancel_token = CancellationToken::new();
-        let files = client.ls(path.to_string(), cancel_token).await?;
-
-        for file in files {
-            let indent = "  ".repeat(depth);
+        let options = WalkOptions {
+            sort: true,
+            ..Default::default()
+        };
+        let entries = client.walk("/remote/directory".to_string(), options, cancel_token);
+        futures::pin_mut!(entries);
+
+        while let Some(entry) = entries.next().await {
+            let entry = entry?;
+            let indent = "  ".repeat(entry.depth - 1);
             println!(
                 "{}{:?} ({} bytes)",
                 indent,
-                file.path,
-                file.size.unwrap_or(0)
+                entry.metadata.path,
+                entry.metadata.size.unwrap_or(0)
             );
-
-            // If you want to recurse into directories, you would do it here
-            // Note: Current implementation only lists regular files
         }
-
-        Ok(())
     }
 
-    list_recursive(&client, "/remote/directory", 0).await?;
-
     client.close().await?;
     manager.close().await?;
 
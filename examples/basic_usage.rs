@@ -1,7 +1,6 @@
 // examples/basic_usage.rs
 // Run with: cargo run --example basic_usage
 
-use anyhow;
 use rusty_sftp::{
     CancellationToken, FileTransferOperationResult, SftpClientConfig, SftpSessionManager,
 };
@@ -26,6 +25,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .create_sftp_client(SftpClientConfig {
             io_size: 65_536, // 64KB buffer size
             concurrency: 10, // 10 concurrent operations
+            ..SftpClientConfig::default()
         })
         .await?;
 
@@ -0,0 +1,111 @@
+#!/usr/bin/env bash
+# Exercises the REST API server against a local sshd.
+#
+# Requires curl, jq and an sshd accepting key authentication for the current
+# user on localhost (the key given with SFTP_TEST_KEY, or the SSH agent).
+#
+#   scripts/test-server.sh
+#
+# Environment:
+#   SFTP_TEST_HOST  server to connect to (default: localhost)
+#   SFTP_TEST_USER  user to connect as (default: $USER)
+#   SFTP_TEST_PORT  SSH port (default: 22)
+#   SFTP_TEST_KEY   private key file (default: the SSH agent)
+#   SERVER_PORT     port of the API server (default: 18080)
+set -euo pipefail
+
+host=${SFTP_TEST_HOST:-localhost}
+user=${SFTP_TEST_USER:-$USER}
+port=${SFTP_TEST_PORT:-22}
+api="http://127.0.0.1:${SERVER_PORT:-18080}"
+
+cd "$(dirname "$0")/.."
+cargo build --quiet --features rest-api --bin rusty-sftp-server
+
+work=$(mktemp -d)
+token_file="$work/token"
+head -c 32 /dev/urandom | od -An -tx1 | tr -d ' \n' > "$token_file"
+token=$(cat "$token_file")
+
+server_args=(--listen "${api#http://}" --allow-host "$user@$host" --auth-token-file "$token_file")
+if [[ -n ${SFTP_TEST_KEY:-} ]]; then
+    server_args+=(-i "$SFTP_TEST_KEY")
+fi
+target/debug/rusty-sftp-server "${server_args[@]}" &
+server=$!
+remote_dir=/tmp/rusty-sftp-test-$$
+cleanup() {
+    curl -s -o /dev/null -X DELETE -H "Authorization: Bearer $token" \
+        "$api/files?$(query "$remote_dir")&recursive=true" || true
+    kill "$server" 2>/dev/null || true
+    wait "$server" 2>/dev/null || true
+    rm -rf "$work"
+}
+trap cleanup EXIT
+
+query() {
+    printf 'host=%s&user=%s&port=%s&path=%s' "$host" "$user" "$port" "$1"
+}
+
+# Runs curl with the token, failing unless the status is the expected one
+request() {
+    local expected=$1
+    shift
+    local status url
+    for url in "$@"; do [[ $url == "$api"/* ]] && break; done
+    status=$(curl -s -o "$work/body" -w '%{http_code}' -H "Authorization: Bearer $token" "$@")
+    if [[ $status != "$expected" ]]; then
+        echo "FAIL: $url returned $status, expected $expected" >&2
+        cat "$work/body" >&2
+        exit 1
+    fi
+    echo "ok: $expected ${url#"$api"}"
+}
+
+for _ in $(seq 50); do
+    curl -s -o /dev/null "$api/jobs" && break
+    sleep 0.1
+done
+
+# Access control
+status=$(curl -s -o /dev/null -w '%{http_code}' "$api/stat?$(query /)")
+[[ $status == 401 ]] || { echo "FAIL: request without token returned $status" >&2; exit 1; }
+echo "ok: 401 without token"
+request 403 "$api/stat?host=example.com&user=$user&path=/"
+request 403 "$api/stat?host=$host&user=-oProxyCommand=true&path=/"
+request 403 -X POST -H 'content-type: application/json' "$api/jobs" -d "$(jq -n \
+    --arg host "$host" --arg user "$user" \
+    '{source: {host: $host, user: $user, path: "/etc/hostname"},
+      destination: {host: "example.com", user: $user, path: "/tmp/x"}}')"
+
+# File endpoints
+head -c 300000 /dev/urandom > "$work/upload"
+request 201 -X POST "$api/mkdir?$(query "$remote_dir/sub")&parents=true"
+request 200 -T "$work/upload" "$api/upload?$(query "$remote_dir/sub/data.bin")"
+request 200 "$api/stat?$(query "$remote_dir/sub/data.bin")"
+[[ $(jq .size "$work/body") == 300000 ]] || { echo "FAIL: wrong size" >&2; exit 1; }
+request 200 "$api/files?$(query "$remote_dir/sub")"
+[[ $(jq length "$work/body") == 1 ]] || { echo "FAIL: wrong listing" >&2; exit 1; }
+request 200 "$api/download?$(query "$remote_dir/sub/data.bin")"
+cmp "$work/body" "$work/upload"
+request 404 "$api/stat?$(query "$remote_dir/missing")"
+
+# Transfer jobs
+request 202 -X POST -H 'content-type: application/json' "$api/jobs" -d "$(jq -n \
+    --arg host "$host" --arg user "$user" --argjson port "$port" --arg dir "$remote_dir" \
+    '{source: {host: $host, user: $user, port: $port, path: "\($dir)/sub/data.bin"},
+      destination: {host: $host, user: $user, port: $port, path: "\($dir)/copy.bin"},
+      verify_checksum: true}')"
+job=$(jq .id "$work/body")
+curl -s -N --max-time 30 -H "Authorization: Bearer $token" "$api/jobs/$job/events" \
+    > "$work/events"
+grep -q '^event: result' "$work/events" || { cat "$work/events" >&2; exit 1; }
+echo "ok: job $job events"
+request 200 "$api/jobs/$job"
+[[ $(jq -r .state "$work/body") == completed ]] || { echo "FAIL: job not completed" >&2; exit 1; }
+request 200 "$api/download?$(query "$remote_dir/copy.bin")"
+cmp "$work/body" "$work/upload"
+
+request 204 -X DELETE "$api/files?$(query "$remote_dir")&recursive=true"
+request 404 "$api/stat?$(query "$remote_dir")"
+echo "All server tests passed"
@@ -0,0 +1,215 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use rusty_sftp::KnownHostsPolicy;
+use std::path::PathBuf;
+
+const EXIT_CODES_HELP: &str = "\
+Exit codes:
+  0    Success
+  1    The command failed
+  2    Invalid arguments
+  3    The connection to the server failed
+  4    A remote path does not exist
+  130  Interrupted with Ctrl-C";
+
+/// SFTP client built on rusty_sftp
+#[derive(Debug, Parser)]
+#[command(
+    name = "rusty-sftp",
+    version,
+    about,
+    after_help = EXIT_CODES_HELP
+)]
+pub(crate) struct Cli {
+    /// Remote server, as `[user@]host`
+    pub(crate) destination: String,
+
+    #[command(flatten)]
+    pub(crate) connection: ConnectionArgs,
+
+    /// Run the shell commands of a batch file (`-` for stdin) instead of a
+    /// single command, stopping at the first failure not prefixed with `-`
+    #[arg(short, long, value_name = "FILE")]
+    pub(crate) batch: Option<PathBuf>,
+
+    /// Define a variable expanded as `$NAME` or `${NAME}` in the batch file
+    /// (repeatable, takes precedence over environment variables)
+    #[arg(short = 'D', long = "define", value_name = "NAME=VALUE", value_parser = parse_variable)]
+    pub(crate) variables: Vec<(String, String)>,
+
+    /// Buffer size for read/write operations in bytes
+    #[arg(long, default_value_t = 65_536, global = true)]
+    pub(crate) io_size: usize,
+
+    /// Number of concurrent operations per transfer
+    #[arg(long, default_value_t = 8, global = true)]
+    pub(crate) concurrency: usize,
+
+    /// Output format of results, progress and errors
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text, global = true)]
+    pub(crate) output: OutputFormat,
+
+    /// Do not display progress bars
+    #[arg(short, long, global = true)]
+    pub(crate) quiet: bool,
+
+    /// Log more details to stderr (-v for info, -vv for debug)
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    pub(crate) verbose: u8,
+
+    /// Command to run; an interactive shell is started when omitted
+    #[command(subcommand)]
+    pub(crate) command: Option<Command>,
+}
+
+/// SSH connection flags
+#[derive(Debug, Args)]
+pub(crate) struct ConnectionArgs {
+    /// SSH port
+    #[arg(short = 'P', long)]
+    pub(crate) port: Option<u16>,
+
+    /// Private key file
+    #[arg(short = 'i', long = "identity")]
+    pub(crate) ssh_key_path: Option<PathBuf>,
+
+    /// Host key checking policy
+    #[arg(long, value_enum, default_value_t = KnownHosts::Add)]
+    pub(crate) known_hosts: KnownHosts,
+
+    /// Directory for SSH control sockets (defaults to the temporary directory)
+    #[arg(long)]
+    pub(crate) control_dir: Option<PathBuf>,
+
+    /// Connection timeout in seconds
+    #[arg(long, default_value_t = 60)]
+    pub(crate) connect_timeout: u64,
+}
+
+/// Host key checking policy
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub(crate) enum KnownHosts {
+    /// Reject unknown hosts and changed keys
+    Strict,
+    /// Add unknown hosts, reject changed keys
+    Add,
+    /// Accept any host key
+    Accept,
+}
+
+impl From<KnownHosts> for KnownHostsPolicy {
+    fn from(known_hosts: KnownHosts) -> Self {
+        match known_hosts {
+            KnownHosts::Strict => KnownHostsPolicy::Strict,
+            KnownHosts::Add => KnownHostsPolicy::Add,
+            KnownHosts::Accept => KnownHostsPolicy::Accept,
+        }
+    }
+}
+
+/// Output format of the command-line tool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum OutputFormat {
+    /// Human-readable text and progress bars
+    Text,
+    /// One JSON document per command on stdout, including errors, and
+    /// NDJSON transfer progress on stderr
+    Json,
+}
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum Command {
+    /// List a remote directory
+    Ls {
+        /// Remote directory or file
+        #[arg(default_value = ".")]
+        path: String,
+        /// Use a long listing format
+        #[arg(short, long)]
+        long: bool,
+        /// Include entries whose name starts with `.`
+        #[arg(short, long)]
+        all: bool,
+    },
+    /// Download a remote file
+    ///
+    /// The file is written to `<local>.part` and renamed once the download
+    /// completes, so a failed download leaves an existing file untouched.
+    Get {
+        /// Remote file
+        remote: String,
+        /// Local destination file or directory (defaults to the current directory)
+        local: Option<String>,
+    },
+    /// Upload a local file
+    Put {
+        /// Local file
+        local: String,
+        /// Remote destination file or directory (defaults to the remote home directory)
+        remote: Option<String>,
+    },
+    /// Remove remote files
+    Rm {
+        /// Remote paths
+        #[arg(required = true)]
+        paths: Vec<String>,
+        /// Remove directories and their contents recursively
+        #[arg(short, long)]
+        recursive: bool,
+    },
+    /// Create a remote directory
+    Mkdir {
+        /// Remote directory
+        path: String,
+        /// Create missing parent directories, no error if it exists
+        #[arg(short, long)]
+        parents: bool,
+    },
+    /// Rename or move a remote file or directory
+    #[command(visible_alias = "rename")]
+    Mv {
+        /// Current remote path
+        src: String,
+        /// New remote path
+        dest: String,
+    },
+    /// Display the metadata of a remote path
+    Stat {
+        /// Remote path
+        path: String,
+        /// Follow symbolic links
+        #[arg(short = 'L', long)]
+        dereference: bool,
+    },
+    /// Mirror a directory tree between local storage and the server
+    Sync(SyncArgs),
+}
+
+#[derive(Debug, Args)]
+pub(crate) struct SyncArgs {
+    /// Local directory
+    pub(crate) local: String,
+    /// Remote directory
+    pub(crate) remote: String,
+    /// Mirror the remote directory onto the local one instead of uploading
+    #[arg(long)]
+    pub(crate) download: bool,
+    /// Delete destination entries missing from the source
+    #[arg(long)]
+    pub(crate) delete: bool,
+    /// Only print the planned actions
+    #[arg(short = 'n', long)]
+    pub(crate) dry_run: bool,
+    /// Compare file contents by checksum instead of modification time
+    #[arg(short, long)]
+    pub(crate) checksum: bool,
+    /// Exclude paths matching a gitignore-style pattern (repeatable)
+    #[arg(long = "exclude", value_name = "PATTERN")]
+    pub(crate) excludes: Vec<String>,
+}
+
+fn parse_variable(definition: &str) -> Result<(String, String), String> {
+    match definition.split_once('=') {
+        Some((name, value)) if !name.is_empty() => Ok((name.to_string(), value.to_string())),
+        _ => Err(format!("expected NAME=VALUE, got {:?}", definition)),
+    }
+}
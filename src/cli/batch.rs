@@ -0,0 +1,232 @@
+use anyhow::{Context, anyhow, bail};
+use rusty_sftp::{SftpClient, SftpError};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+
+use crate::cli::format::Output;
+use crate::cli::shell::{self, PROMPT};
+
+/// Batch script read from a file or standard input
+#[derive(Debug)]
+pub(crate) struct Script {
+    /// File name used in error messages
+    name: String,
+    lines: Vec<String>,
+}
+
+/// Reads a batch script, `-` meaning standard input
+pub(crate) fn read_script(path: &Path) -> Result<Script, anyhow::Error> {
+    let (name, contents) = if path == Path::new("-") {
+        let mut contents = String::new();
+        std::io::stdin()
+            .read_to_string(&mut contents)
+            .context("Failed to read batch script from stdin")?;
+        ("<stdin>".to_string(), contents)
+    } else {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read batch script {}", path.display()))?;
+        (path.display().to_string(), contents)
+    };
+    Ok(Script {
+        name,
+        lines: contents.lines().map(str::to_string).collect(),
+    })
+}
+
+/// Runs a batch script like `sftp -b`
+///
+/// Each line is a shell command, echoed to stdout before it runs (except
+/// with `--output json`). Blank lines and lines starting with `#` are
+/// skipped. Prefixes, in any order:
+/// - `-` ignores a failure of the command and continues with the next line
+/// - `@` does not echo the command
+///
+/// `$NAME` and `${NAME}` are replaced by the value given with `--define`, or
+/// else the environment variable of that name; `$$` is a literal `$`.
+///
+/// # Errors
+///
+/// Returns the first error of a command without the `-` prefix, with the
+/// script name and line number as context. Cancelling with Ctrl-C always stops
+/// the script.
+pub(crate) async fn run(
+    client: &mut SftpClient,
+    script: Script,
+    variables: &HashMap<String, String>,
+    output: Output,
+) -> Result<(), anyhow::Error> {
+    let home = client.set_current_dir(".".to_string()).await?;
+
+    for (index, line) in script.lines.iter().enumerate() {
+        let location = format!("{}:{}", script.name, index + 1);
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (ignore_errors, echo, command) = parse_prefixes(line);
+        // Echoed commands would break the JSON output
+        if echo && !output.is_json() {
+            println!("{}{}", PROMPT, command);
+        }
+
+        let result = match expand(command, variables) {
+            Ok(command) => shell::execute_line(client, &command, &home, output).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(e) if e.downcast_ref::<SftpError>() == Some(&SftpError::Cancelled) => {
+                return Err(e.context(location));
+            }
+            Err(e) if ignore_errors && output.is_json() => output.print_error(&e.context(location)),
+            Err(e) if ignore_errors => eprintln!("{}: {:#} (ignored)", location, e),
+            Err(e) => return Err(e.context(location)),
+        }
+    }
+    Ok(())
+}
+
+/// Strips the `-` and `@` prefixes of a line, returning whether errors are
+/// ignored, whether the command is echoed and the command itself
+fn parse_prefixes(line: &str) -> (bool, bool, &str) {
+    let (mut ignore_errors, mut echo, mut command) = (false, true, line);
+    loop {
+        if let Some(rest) = command.strip_prefix('-') {
+            ignore_errors = true;
+            command = rest;
+        } else if let Some(rest) = command.strip_prefix('@') {
+            echo = false;
+            command = rest;
+        } else {
+            break;
+        }
+    }
+    (ignore_errors, echo, command.trim_start())
+}
+
+/// Expands `$NAME`, `${NAME}` and `$$` in a command line
+fn expand(line: &str, variables: &HashMap<String, String>) -> Result<String, anyhow::Error> {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut expanded = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(dollar) = rest.find('$') {
+        expanded.push_str(&rest[..dollar]);
+        rest = &rest[dollar + 1..];
+
+        let (name, remaining) = if let Some(braced) = rest.strip_prefix('{') {
+            let end = braced
+                .find('}')
+                .ok_or_else(|| anyhow!("Unterminated variable reference in {:?}", line))?;
+            (&braced[..end], &braced[end + 1..])
+        } else if let Some(remaining) = rest.strip_prefix('$') {
+            expanded.push('$');
+            rest = remaining;
+            continue;
+        } else {
+            let end = rest.find(|c| !is_name_char(c)).unwrap_or(rest.len());
+            (&rest[..end], &rest[end..])
+        };
+        if name.is_empty() {
+            // A lone `$` is kept as is
+            expanded.push('$');
+            continue;
+        }
+        if !name.chars().all(is_name_char) {
+            bail!("Invalid variable name {:?}", name);
+        }
+        let value = match variables.get(name) {
+            Some(value) => value.clone(),
+            None => std::env::var(name).map_err(|_| anyhow!("Undefined variable {:?}", name))?,
+        };
+        expanded.push_str(&value);
+        rest = remaining;
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variables() -> HashMap<String, String> {
+        HashMap::from([
+            ("DIR".to_string(), "/data/in".to_string()),
+            ("DATE".to_string(), "2026-10-18".to_string()),
+        ])
+    }
+
+    #[test]
+    fn expands_plain_and_braced_names() {
+        let variables = variables();
+        assert_eq!(expand("cd $DIR", &variables).unwrap(), "cd /data/in");
+        assert_eq!(
+            expand("get ${DIR}/report_${DATE}.csv", &variables).unwrap(),
+            "get /data/in/report_2026-10-18.csv"
+        );
+        // A plain name ends at the first character that cannot be part of it
+        assert_eq!(
+            expand("get $DIR/$DATE.csv", &variables).unwrap(),
+            "get /data/in/2026-10-18.csv"
+        );
+        assert_eq!(expand("ls", &variables).unwrap(), "ls");
+    }
+
+    #[test]
+    fn defined_variables_take_precedence_over_the_environment() {
+        let variables = HashMap::from([("PATH".to_string(), "/defined".to_string())]);
+        assert_eq!(expand("cd $PATH", &variables).unwrap(), "cd /defined");
+    }
+
+    #[test]
+    fn double_dollar_is_a_literal_dollar() {
+        let variables = variables();
+        assert_eq!(expand("get a$$b", &variables).unwrap(), "get a$b");
+        assert_eq!(expand("get $$DIR", &variables).unwrap(), "get $DIR");
+        assert_eq!(expand("get $$$DIR", &variables).unwrap(), "get $/data/in");
+    }
+
+    #[test]
+    fn lone_dollar_is_kept() {
+        let variables = variables();
+        assert_eq!(expand("get price$", &variables).unwrap(), "get price$");
+        assert_eq!(expand("get a$-b", &variables).unwrap(), "get a$-b");
+    }
+
+    #[test]
+    fn undefined_variable_is_an_error() {
+        let error = expand("cd $RUSTY_SFTP_TEST_UNDEFINED", &variables()).unwrap_err();
+        assert!(
+            error.to_string().contains("Undefined variable"),
+            "{}",
+            error
+        );
+        assert!(expand("cd ${RUSTY_SFTP_TEST_UNDEFINED}", &variables()).is_err());
+    }
+
+    #[test]
+    fn unterminated_or_invalid_braces_are_errors() {
+        let error = expand("cd ${DIR", &variables()).unwrap_err();
+        assert!(error.to_string().contains("Unterminated"), "{}", error);
+        let error = expand("cd ${DIR-x}", &variables()).unwrap_err();
+        assert!(
+            error.to_string().contains("Invalid variable name"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn parses_prefixes_in_any_order() {
+        assert_eq!(parse_prefixes("ls"), (false, true, "ls"));
+        assert_eq!(parse_prefixes("-rm old"), (true, true, "rm old"));
+        assert_eq!(parse_prefixes("@cd /tmp"), (false, false, "cd /tmp"));
+        assert_eq!(parse_prefixes("-@rm old"), (true, false, "rm old"));
+        assert_eq!(parse_prefixes("@- rm old"), (true, false, "rm old"));
+        // Only leading prefixes are stripped
+        assert_eq!(parse_prefixes("rm -f old"), (false, true, "rm -f old"));
+    }
+}
@@ -0,0 +1,384 @@
+use anyhow::{Context, anyhow, bail};
+use futures::TryStreamExt;
+use rusty_sftp::{
+    CancellationToken, ConnectOptions, FileFilter, FileMetadata, FileTransferOperationResult,
+    FileType, SftpClient, SftpClientConfig, SftpError, SftpSessionManager, SyncDirection,
+    SyncOptions, WalkOptions,
+};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::warn;
+
+use crate::cli::args::{Cli, Command, ConnectionArgs, SyncArgs};
+use crate::cli::format::{Output, display_name, long_entry, print_json, stat_entry};
+use crate::cli::{batch, shell};
+
+/// Connects to the server, runs the command, the batch script or (when
+/// neither is given) the interactive shell, and closes the connection
+pub(crate) async fn run(cli: Cli) -> Result<(), anyhow::Error> {
+    // Read the script first so that a missing file fails before connecting
+    let script = cli.batch.as_deref().map(batch::read_script).transpose()?;
+
+    let cancel_token = CancellationToken::new();
+    let connecting = async {
+        tokio::select! {
+            _ = cancel_token.cancelled() => Err(SftpError::Cancelled.into()),
+            connected = connect(&cli) => connected,
+        }
+    };
+    let (mut manager, mut client) = interruptible(connecting, &cancel_token).await?;
+    let output = Output::from(&cli);
+
+    let result = match (cli.command, script) {
+        (Some(command), _) => {
+            let running = execute(&client, command, output, cancel_token.clone());
+            interruptible(running, &cancel_token).await
+        }
+        (None, Some(script)) => {
+            let variables = cli.variables.into_iter().collect();
+            batch::run(&mut client, script, &variables, output).await
+        }
+        (None, None) => shell::run(&mut client, output).await,
+    };
+
+    if let Err(e) = client.close().await {
+        warn!("Failed to close sftp client: {:#}", e);
+    }
+    if let Err(e) = manager.close().await {
+        warn!("Failed to close ssh session: {:#}", e);
+    }
+    result
+}
+
+/// Drives `future` to completion, cancelling `cancel_token` when Ctrl-C is
+/// pressed so that the operation can clean up and return
+/// `SftpError::Cancelled`
+pub(crate) async fn interruptible<F: Future>(
+    future: F,
+    cancel_token: &CancellationToken,
+) -> F::Output {
+    tokio::pin!(future);
+    loop {
+        tokio::select! {
+            output = &mut future => return output,
+            interrupted = tokio::signal::ctrl_c(), if !cancel_token.is_cancelled() => {
+                if interrupted.is_ok() {
+                    cancel_token.cancel();
+                }
+            }
+        }
+    }
+}
+
+async fn connect(cli: &Cli) -> Result<(SftpSessionManager, SftpClient), anyhow::Error> {
+    let (username, host) = parse_destination(&cli.destination)?;
+    let connection_failed = |reason: String| SftpError::ConnectionFailed {
+        host: host.clone(),
+        reason,
+    };
+    let manager = SftpSessionManager::connect_with_options(
+        &host,
+        &username,
+        connect_options(&cli.connection),
+    )
+    .await
+    .map_err(|e| connection_failed(e.to_string()))?;
+    let client = manager
+        .create_sftp_client(SftpClientConfig::new(cli.io_size, cli.concurrency))
+        .await
+        .map_err(|e| connection_failed(format!("{:#}", e)))?;
+    Ok((manager, client))
+}
+
+/// Splits `[user@]host`, falling back to `$USER` when no user is given
+fn parse_destination(destination: &str) -> Result<(String, String), anyhow::Error> {
+    if let Some((username, host)) = destination.rsplit_once('@') {
+        return Ok((username.to_string(), host.to_string()));
+    }
+    let username = std::env::var("USER")
+        .map_err(|_| anyhow!("No user given in {:?} and $USER is not set", destination))?;
+    Ok((username, destination.to_string()))
+}
+
+fn connect_options(args: &ConnectionArgs) -> ConnectOptions {
+    ConnectOptions {
+        port: args.port,
+        ssh_key_path: args.ssh_key_path.clone(),
+        control_dir: args.control_dir.clone(),
+        known_hosts: args.known_hosts.into(),
+        connect_timeout: Duration::from_secs(args.connect_timeout),
+    }
+}
+
+pub(crate) async fn execute(
+    client: &SftpClient,
+    command: Command,
+    output: Output,
+    cancel_token: CancellationToken,
+) -> Result<(), anyhow::Error> {
+    match command {
+        Command::Ls { path, long, all } => ls(client, path, long, all, output, cancel_token).await,
+        Command::Get { remote, local } => get(client, remote, local, output, cancel_token).await,
+        Command::Put { local, remote } => put(client, local, remote, output, cancel_token).await,
+        Command::Rm { paths, recursive } => {
+            rm(client, paths.clone(), recursive, cancel_token).await?;
+            output.print_done(json!({ "paths": paths }))
+        }
+        Command::Mkdir { path, parents } => {
+            if parents {
+                client.create_dir_all(path.clone()).await?;
+            } else {
+                client.create_dir(path.clone()).await?;
+            }
+            output.print_done(json!({ "path": path }))
+        }
+        Command::Mv { src, dest } => {
+            client.rename(src.clone(), dest.clone()).await?;
+            output.print_done(json!({ "src": src, "dest": dest }))
+        }
+        Command::Stat { path, dereference } => {
+            let file = if dereference {
+                client.stat(path).await?
+            } else {
+                client.lstat(path).await?
+            };
+            if output.is_json() {
+                print_json(&file)
+            } else {
+                println!("{}", stat_entry(&file));
+                Ok(())
+            }
+        }
+        Command::Sync(args) => sync(client, args, output, cancel_token).await,
+    }
+}
+
+async fn ls(
+    client: &SftpClient,
+    path: String,
+    long: bool,
+    all: bool,
+    output: Output,
+    cancel_token: CancellationToken,
+) -> Result<(), anyhow::Error> {
+    let mut files = Vec::new();
+    let target = client.stat(path.clone()).await?;
+    if target.file_type != FileType::Directory {
+        let file = client.lstat(path.clone()).await?;
+        files.push((file, path));
+    } else {
+        let options = WalkOptions {
+            max_depth: Some(1),
+            sort: true,
+            ..WalkOptions::default()
+        };
+        let entries = client.walk(path, options, cancel_token);
+        tokio::pin!(entries);
+        while let Some(entry) = entries.try_next().await? {
+            let name = display_name(&entry.metadata);
+            if all || !name.starts_with('.') {
+                files.push((entry.metadata, name));
+            }
+        }
+    }
+
+    if output.is_json() {
+        let files: Vec<FileMetadata> = files.into_iter().map(|(file, _)| file).collect();
+        return print_json(&files);
+    }
+    for (file, name) in files {
+        if long {
+            println!("{}", long_entry(&file, &name));
+        } else {
+            println!("{}", name);
+        }
+    }
+    Ok(())
+}
+
+async fn get(
+    client: &SftpClient,
+    remote: String,
+    local: Option<String>,
+    output: Output,
+    cancel_token: CancellationToken,
+) -> Result<(), anyhow::Error> {
+    let file = client.stat(remote.clone()).await?;
+    if file.file_type == FileType::Directory {
+        bail!("{}: is a directory", remote);
+    }
+    let name = display_name(&file);
+    let local = match local.map(PathBuf::from) {
+        Some(local) if local.is_dir() => local.join(&name),
+        Some(local) => local,
+        None => PathBuf::from(&name),
+    };
+    // Downloaded next to the destination and renamed once complete, so that
+    // a failed or cancelled download leaves an existing file untouched
+    let mut partial = local.clone().into_os_string();
+    partial.push(".part");
+    let partial = PathBuf::from(partial);
+    let writer = tokio::fs::File::create(&partial)
+        .await
+        .with_context(|| format!("Failed to create local file {}", partial.display()))?;
+
+    let src_file = client.resolve_path(remote);
+    let dest_file = local.display().to_string();
+    let progress = output.progress(&name, &src_file, &dest_file, file.size);
+    let writer = progress.bar().wrap_async_write(writer);
+    let result = client
+        .get_to_writer(src_file.clone(), writer, cancel_token)
+        .await;
+    let result = match progress.finish(result).await {
+        Ok(result) => tokio::fs::rename(&partial, &local)
+            .await
+            .map(|()| result)
+            .with_context(|| format!("Failed to rename {} to {}", partial.display(), dest_file)),
+        Err(e) => Err(e),
+    };
+    if result.is_err()
+        && let Err(e) = tokio::fs::remove_file(&partial).await
+    {
+        warn!("Failed to remove {}: {}", partial.display(), e);
+    }
+    print_transfer(output, result?, src_file, dest_file)
+}
+
+async fn put(
+    client: &SftpClient,
+    local: String,
+    remote: Option<String>,
+    output: Output,
+    cancel_token: CancellationToken,
+) -> Result<(), anyhow::Error> {
+    let local = Path::new(&local);
+    let name = local
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| anyhow!("{}: not a file", local.display()))?;
+    let reader = tokio::fs::File::open(local)
+        .await
+        .with_context(|| format!("Failed to open local file {}", local.display()))?;
+    let metadata = reader.metadata().await?;
+    if metadata.is_dir() {
+        bail!("{}: is a directory", local.display());
+    }
+
+    let remote = match remote {
+        Some(remote) => match client.stat(remote.clone()).await {
+            Ok(dir) if dir.file_type == FileType::Directory => {
+                format!("{}/{}", remote.trim_end_matches('/'), name)
+            }
+            Ok(_) => remote,
+            Err(e) if matches!(e.downcast_ref(), Some(SftpError::NotFound { .. })) => remote,
+            Err(e) => return Err(e),
+        },
+        None => name.clone(),
+    };
+
+    let src_file = local.display().to_string();
+    let dest_file = client.resolve_path(remote);
+    let progress = output.progress(&name, &src_file, &dest_file, Some(metadata.len()));
+    let reader = progress.bar().wrap_async_read(reader);
+    let result = client
+        .put_from_reader(
+            reader,
+            dest_file.clone(),
+            Some(metadata.len()),
+            cancel_token,
+        )
+        .await;
+    print_transfer(output, progress.finish(result).await?, src_file, dest_file)
+}
+
+/// Prints the result of a completed transfer in JSON mode (text mode only
+/// shows the progress bar), naming the local file instead of the
+/// reader or writer it was streamed through
+fn print_transfer(
+    output: Output,
+    mut result: FileTransferOperationResult,
+    src_file: String,
+    dest_file: String,
+) -> Result<(), anyhow::Error> {
+    if !output.is_json() {
+        return Ok(());
+    }
+    if let FileTransferOperationResult::Completed(completed) = &mut result {
+        completed.src_file = src_file;
+        completed.dest_file = dest_file;
+    }
+    print_json(&result)
+}
+
+async fn rm(
+    client: &SftpClient,
+    paths: Vec<String>,
+    recursive: bool,
+    cancel_token: CancellationToken,
+) -> Result<(), anyhow::Error> {
+    for path in paths {
+        let file = client.lstat(path.clone()).await?;
+        if file.file_type != FileType::Directory {
+            client.remove_file(path).await?;
+        } else if recursive {
+            client.remove_dir_all(path, cancel_token.clone()).await?;
+        } else {
+            bail!("{}: is a directory (use -r to remove it)", path);
+        }
+    }
+    Ok(())
+}
+
+async fn sync(
+    client: &SftpClient,
+    args: SyncArgs,
+    output: Output,
+    cancel_token: CancellationToken,
+) -> Result<(), anyhow::Error> {
+    let filter = if args.excludes.is_empty() {
+        None
+    } else {
+        let filter = args
+            .excludes
+            .iter()
+            .try_fold(FileFilter::new(), |filter, pattern| filter.exclude(pattern))?;
+        Some(filter)
+    };
+    let direction = if args.download {
+        SyncDirection::Download
+    } else {
+        SyncDirection::Upload
+    };
+    let options = SyncOptions {
+        delete: args.delete,
+        dry_run: args.dry_run,
+        checksum: args.checksum,
+        filter,
+    };
+
+    let report = client
+        .sync(args.local, args.remote, direction, options, cancel_token)
+        .await?;
+    if output.is_json() {
+        print_json(&report)?;
+    } else {
+        for action in &report.actions {
+            println!("{}", action);
+        }
+    }
+    if report.cancelled {
+        return Err(SftpError::Cancelled.into());
+    }
+    Ok(())
+}
+
+/// Maps an error to the exit codes documented in `--help`
+pub(crate) fn exit_code(error: &anyhow::Error) -> u8 {
+    match error.downcast_ref::<SftpError>() {
+        Some(SftpError::ConnectionFailed { .. }) => 3,
+        Some(SftpError::NotFound { .. }) => 4,
+        Some(SftpError::Cancelled) => 130,
+        _ => 1,
+    }
+}
@@ -0,0 +1,370 @@
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use rusty_sftp::{
+    FileMetadata, FileTransferOperationResult, FileTransferProgress, FileType, SftpError,
+};
+use serde::Serialize;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::task::JoinHandle;
+
+use crate::cli::args::{Cli, OutputFormat};
+use crate::cli::commands::exit_code;
+
+const BAR_TEMPLATE: &str = "{msg} [{bar:30}] {bytes}/{total_bytes} {bytes_per_sec} eta {eta}";
+const SPINNER_TEMPLATE: &str = "{spinner} {msg} {bytes} {bytes_per_sec}";
+/// Interval between two NDJSON progress lines
+const JSON_PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How command results, progress and errors are displayed
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Output {
+    pub(crate) format: OutputFormat,
+    pub(crate) quiet: bool,
+}
+
+impl From<&Cli> for Output {
+    fn from(cli: &Cli) -> Self {
+        Self {
+            format: cli.output,
+            quiet: cli.quiet,
+        }
+    }
+}
+
+impl Output {
+    pub(crate) fn is_json(&self) -> bool {
+        self.format == OutputFormat::Json
+    }
+
+    /// Starts displaying the progress of a transfer of `size` bytes: a
+    /// progress bar in text mode, NDJSON `FileTransferProgress` lines on
+    /// stderr in JSON mode, nothing when quiet
+    pub(crate) fn progress(
+        &self,
+        name: &str,
+        src_file: &str,
+        dest_file: &str,
+        size: Option<u64>,
+    ) -> TransferProgress {
+        let bar = progress_bar(name, size);
+        if self.quiet || self.is_json() {
+            bar.set_draw_target(ProgressDrawTarget::hidden());
+        }
+        let reporter = (self.is_json() && !self.quiet)
+            .then(|| spawn_json_progress(bar.clone(), src_file.to_string(), dest_file.to_string()));
+        TransferProgress { bar, reporter }
+    }
+
+    /// Prints the JSON document of a command without output in text mode, with
+    /// `"ok": true` added to the fields of `document`
+    pub(crate) fn print_done(&self, mut document: serde_json::Value) -> Result<(), anyhow::Error> {
+        if !self.is_json() {
+            return Ok(());
+        }
+        document["ok"] = true.into();
+        print_json(&document)
+    }
+
+    /// Prints an error: as text on stderr, or as a JSON document on stdout
+    pub(crate) fn print_error(&self, error: &anyhow::Error) {
+        if !self.is_json() {
+            match error.downcast_ref::<clap::Error>() {
+                Some(usage) => {
+                    let _ = usage.print();
+                }
+                None => eprintln!("rusty-sftp: {:#}", error),
+            }
+            return;
+        }
+        let report = ErrorReport {
+            error: format!("{:#}", error),
+            exit_code: exit_code(error),
+            details: error.downcast_ref::<SftpError>(),
+        };
+        if let Err(e) = print_json(&report) {
+            eprintln!("rusty-sftp: {:#} ({:#})", error, e);
+        }
+    }
+}
+
+/// JSON document printed for a failed command
+#[derive(Debug, Serialize)]
+struct ErrorReport<'a> {
+    error: String,
+    exit_code: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<&'a SftpError>,
+}
+
+/// Prints a value as a single line of JSON on stdout
+pub(crate) fn print_json(value: &impl Serialize) -> Result<(), anyhow::Error> {
+    println!("{}", serde_json::to_string(value)?);
+    Ok(())
+}
+
+/// Progress display of a running transfer
+pub(crate) struct TransferProgress {
+    bar: ProgressBar,
+    /// Task printing NDJSON progress lines in JSON mode
+    reporter: Option<JoinHandle<()>>,
+}
+
+impl TransferProgress {
+    /// Progress bar to wrap the transferred reader or writer with
+    pub(crate) fn bar(&self) -> &ProgressBar {
+        &self.bar
+    }
+
+    /// Stops displaying progress, turning a cancelled transfer into an error
+    pub(crate) async fn finish(
+        self,
+        result: Result<FileTransferOperationResult, anyhow::Error>,
+    ) -> Result<FileTransferOperationResult, anyhow::Error> {
+        let result = match result {
+            Ok(FileTransferOperationResult::Completed(completed)) => {
+                self.bar.finish();
+                Ok(FileTransferOperationResult::Completed(completed))
+            }
+            Ok(_) => {
+                self.bar.abandon();
+                Err(SftpError::Cancelled.into())
+            }
+            Err(e) => {
+                self.bar.abandon();
+                Err(e)
+            }
+        };
+        if let Some(reporter) = self.reporter {
+            // The reporter prints a last line once the bar is finished
+            let _ = reporter.await;
+        }
+        result
+    }
+}
+
+/// Creates a progress bar for a transfer of `size` bytes. An unknown size
+/// shows a spinner instead of a bar.
+fn progress_bar(name: &str, size: Option<u64>) -> ProgressBar {
+    let pb = match size {
+        Some(size) => ProgressBar::new(size).with_style(
+            ProgressStyle::with_template(BAR_TEMPLATE)
+                .expect("valid progress bar template")
+                .progress_chars("=> "),
+        ),
+        None => ProgressBar::new_spinner().with_style(
+            ProgressStyle::with_template(SPINNER_TEMPLATE).expect("valid spinner template"),
+        ),
+    };
+    pb.with_message(name.to_string())
+}
+
+/// Periodically prints the position of `bar` as NDJSON `FileTransferProgress`
+/// lines on stderr until the bar is finished
+fn spawn_json_progress(bar: ProgressBar, src_file: String, dest_file: String) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(JSON_PROGRESS_INTERVAL);
+        loop {
+            interval.tick().await;
+            let finished = bar.is_finished();
+            let bytes_transferred = bar.position();
+            let file_size = bar.length().unwrap_or(bytes_transferred);
+            let percentage_progress = if file_size == 0 {
+                100.0
+            } else {
+                bytes_transferred as f64 * 100.0 / file_size as f64
+            };
+            let progress = FileTransferProgress {
+                src_file: src_file.clone(),
+                dest_file: dest_file.clone(),
+                file_size,
+                bytes_transferred,
+                percentage_progress,
+            };
+            if let Ok(line) = serde_json::to_string(&progress) {
+                eprintln!("{}", line);
+            }
+            if finished {
+                break;
+            }
+        }
+    })
+}
+
+/// Formats an entry named `name` as one line of `ls -l` output
+pub(crate) fn long_entry(file: &FileMetadata, name: &str) -> String {
+    let mut line = format!(
+        "{} {:>5} {:>5} {:>12} {} {}",
+        mode_string(file.file_type, file.permissions),
+        id_string(file.uid),
+        id_string(file.gid),
+        file.size.map(|s| s.to_string()).unwrap_or_default(),
+        time_string(file.last_modified_at),
+        name,
+    );
+    if let Some(target) = &file.link_target {
+        line.push_str(&format!(" -> {}", target.display()));
+    }
+    line
+}
+
+/// Formats the metadata of a path as the multi-line output of `stat`
+pub(crate) fn stat_entry(file: &FileMetadata) -> String {
+    let file_type = match file.file_type {
+        FileType::Regular => "regular file",
+        FileType::Directory => "directory",
+        FileType::Symlink => "symbolic link",
+    };
+    let mut lines = vec![format!("  File: {}", file.path.display())];
+    if let Some(target) = &file.link_target {
+        lines.push(format!("  Link: {}", target.display()));
+    }
+    lines.push(format!(
+        "  Size: {:<12} Type: {}",
+        file.size.map(|s| s.to_string()).unwrap_or_default(),
+        file_type
+    ));
+    lines.push(format!(
+        "Access: ({:04o}/{})  Uid: {}  Gid: {}",
+        file.permissions.unwrap_or(0) & 0o7777,
+        mode_string(file.file_type, file.permissions),
+        id_string(file.uid),
+        id_string(file.gid),
+    ));
+    lines.push(format!("Access: {}", time_string(file.last_accessed_at)));
+    lines.push(format!("Modify: {}", time_string(file.last_modified_at)));
+    lines.join("\n")
+}
+
+/// Name of an entry as shown in listings: the last path component
+pub(crate) fn display_name(file: &FileMetadata) -> String {
+    match file.path.file_name() {
+        Some(name) => name.to_string_lossy().into_owned(),
+        None => file.path.display().to_string(),
+    }
+}
+
+/// Formats a file type and permission bits like `ls -l`, e.g. `drwxr-xr-x`
+fn mode_string(file_type: FileType, permissions: Option<u32>) -> String {
+    let mut mode = String::with_capacity(10);
+    mode.push(match file_type {
+        FileType::Regular => '-',
+        FileType::Directory => 'd',
+        FileType::Symlink => 'l',
+    });
+    let Some(permissions) = permissions else {
+        mode.push_str("?????????");
+        return mode;
+    };
+    // (read/write/execute shift, special bit, special char when executable, when not)
+    let classes = [
+        (6, 0o4000, 's', 'S'),
+        (3, 0o2000, 's', 'S'),
+        (0, 0o1000, 't', 'T'),
+    ];
+    for (shift, special, set, unset) in classes {
+        let bits = (permissions >> shift) & 0o7;
+        mode.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        mode.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        let execute = bits & 0o1 != 0;
+        mode.push(match (permissions & special != 0, execute) {
+            (true, true) => set,
+            (true, false) => unset,
+            (false, true) => 'x',
+            (false, false) => '-',
+        });
+    }
+    mode
+}
+
+fn id_string(id: Option<u32>) -> String {
+    id.map(|id| id.to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// Formats a timestamp as `YYYY-MM-DD HH:MM` in UTC
+fn time_string(time: Option<SystemTime>) -> String {
+    let Some(secs) = time
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+    else {
+        return "-".repeat(16);
+    };
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let secs_of_day = secs % 86_400;
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60
+    )
+}
+
+/// Converts days since the Unix epoch to a (year, month, day) civil date
+/// (Howard Hinnant's `civil_from_days` algorithm)
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mode_string_formats_permission_bits() {
+        assert_eq!(mode_string(FileType::Regular, Some(0o644)), "-rw-r--r--");
+        assert_eq!(mode_string(FileType::Directory, Some(0o755)), "drwxr-xr-x");
+        assert_eq!(mode_string(FileType::Symlink, Some(0o777)), "lrwxrwxrwx");
+        assert_eq!(mode_string(FileType::Regular, None), "-?????????");
+    }
+
+    #[test]
+    fn mode_string_formats_special_bits() {
+        // Lowercase when the matching execute bit is set, uppercase otherwise
+        assert_eq!(mode_string(FileType::Regular, Some(0o4755)), "-rwsr-xr-x");
+        assert_eq!(mode_string(FileType::Regular, Some(0o4644)), "-rwSr--r--");
+        assert_eq!(mode_string(FileType::Regular, Some(0o2755)), "-rwxr-sr-x");
+        assert_eq!(mode_string(FileType::Regular, Some(0o2745)), "-rwxr-Sr-x");
+        assert_eq!(mode_string(FileType::Directory, Some(0o1777)), "drwxrwxrwt");
+        assert_eq!(mode_string(FileType::Directory, Some(0o1776)), "drwxrwxrwT");
+        assert_eq!(mode_string(FileType::Regular, Some(0o7000)), "---S--S--T");
+        assert_eq!(mode_string(FileType::Regular, Some(0o7111)), "---s--s--t");
+    }
+
+    #[test]
+    fn civil_from_days_around_the_epoch() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(20_744), (2026, 10, 18));
+        assert_eq!(civil_from_days(-719_162), (1, 1, 1));
+    }
+
+    #[test]
+    fn civil_from_days_handles_leap_days() {
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        assert_eq!(civil_from_days(-672), (1968, 2, 29));
+        // 2000 is a leap year, 1900 and 2100 are not
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(11_017), (2000, 3, 1));
+        assert_eq!(civil_from_days(-25_509), (1900, 2, 28));
+        assert_eq!(civil_from_days(-25_508), (1900, 3, 1));
+        assert_eq!(civil_from_days(47_541), (2100, 3, 1));
+    }
+
+    #[test]
+    fn time_string_formats_utc() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_709_213_820);
+        assert_eq!(time_string(Some(time)), "2024-02-29 13:37");
+        assert_eq!(time_string(Some(UNIX_EPOCH)), "1970-01-01 00:00");
+        assert_eq!(time_string(None), "----------------");
+    }
+}
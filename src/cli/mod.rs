@@ -0,0 +1,10 @@
+/// Module for command-line argument definitions
+pub(crate) mod args;
+/// Module for running batch scripts
+pub(crate) mod batch;
+/// Module for executing commands against the server
+pub(crate) mod commands;
+/// Module for formatting listings, metadata and progress bars
+pub(crate) mod format;
+/// Module for the interactive shell
+pub(crate) mod shell;
@@ -0,0 +1,308 @@
+use anyhow::{Context as _, anyhow};
+use clap::{CommandFactory, Parser, Subcommand};
+use futures::StreamExt;
+use rusty_sftp::{CancellationToken, FileType, SftpClient, WalkOptions};
+use rustyline::completion::{Completer, FilenameCompleter, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use std::path::{Path, PathBuf};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::cli::args::Command;
+use crate::cli::commands::{execute, interruptible};
+use crate::cli::format::{Output, display_name, print_json};
+
+pub(crate) const PROMPT: &str = "sftp> ";
+/// History file, relative to the home directory
+const HISTORY_FILE: &str = ".rusty_sftp_history";
+
+/// One line entered in the interactive shell
+#[derive(Debug, Parser)]
+#[command(multicall = true)]
+struct ShellLine {
+    #[command(subcommand)]
+    command: ShellCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum ShellCommand {
+    /// Change the remote working directory (defaults to the login directory)
+    Cd { path: Option<String> },
+    /// Change the local working directory (defaults to the home directory)
+    Lcd { path: Option<PathBuf> },
+    /// Print the remote working directory
+    Pwd,
+    /// Print the local working directory
+    Lpwd,
+    /// Leave the shell
+    #[command(visible_aliases = ["quit", "bye"])]
+    Exit,
+    #[command(flatten)]
+    Remote(Command),
+}
+
+/// Request from the line editor thread to list a remote directory for tab completion
+struct CompletionRequest {
+    dir: String,
+    /// Names of the entries and whether each one is a directory
+    reply: oneshot::Sender<Vec<(String, bool)>>,
+}
+
+/// Runs an interactive shell similar to the OpenSSH `sftp` client
+///
+/// Relative remote paths are resolved against the remote working directory
+/// (`cd`), relative local paths against the process working directory (`lcd`).
+/// Ctrl-C cancels the running command and returns to the prompt, Ctrl-D leaves.
+pub(crate) async fn run(client: &mut SftpClient, output: Output) -> Result<(), anyhow::Error> {
+    let home = client.set_current_dir(".".to_string()).await?;
+
+    let (completion_tx, mut completion_rx) = mpsc::channel(1);
+    let mut editor = Editor::<ShellHelper, DefaultHistory>::new()?;
+    editor.set_helper(Some(ShellHelper::new(completion_tx)));
+    let history = std::env::var_os("HOME").map(|home| PathBuf::from(home).join(HISTORY_FILE));
+    if let Some(history) = &history {
+        // A missing history file is expected on first use
+        let _ = editor.load_history(history);
+    }
+
+    loop {
+        // The editor blocks on the terminal, so it runs on a blocking thread
+        // while this task serves its remote completion requests
+        let readline = tokio::task::spawn_blocking(move || {
+            let line = editor.readline(PROMPT);
+            (editor, line)
+        });
+        tokio::pin!(readline);
+        let (returned, line) = loop {
+            tokio::select! {
+                joined = &mut readline => break joined?,
+                Some(request) = completion_rx.recv() => {
+                    let names = remote_names(client, request.dir).await;
+                    let _ = request.reply.send(names);
+                }
+            }
+        };
+        editor = returned;
+
+        let line = match line {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e.into()),
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(line);
+
+        match execute_line(client, line, &home, output).await {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(e) => output.print_error(&e),
+        }
+    }
+
+    if let Some(history) = &history
+        && let Err(e) = editor.save_history(history)
+    {
+        tracing::warn!("Failed to save shell history to {:?}: {}", history, e);
+    }
+    Ok(())
+}
+
+/// Executes one shell line, returning `false` when the shell should exit
+///
+/// # Errors
+///
+/// Returns the error of the command, or a `clap::Error` if the line is not a
+/// valid command
+pub(crate) async fn execute_line(
+    client: &mut SftpClient,
+    line: &str,
+    home: &Path,
+    output: Output,
+) -> Result<bool, anyhow::Error> {
+    let words = shell_words::split(line)?;
+    let command = match ShellLine::try_parse_from(words) {
+        Ok(parsed) => parsed.command,
+        Err(e) if !e.use_stderr() => {
+            // `help` output
+            e.print()?;
+            return Ok(true);
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    match command {
+        ShellCommand::Cd { path } => {
+            let path = path.unwrap_or_else(|| home.to_string_lossy().into_owned());
+            let cwd = client.set_current_dir(path).await?;
+            output.print_done(serde_json::json!({ "path": cwd }))?;
+        }
+        ShellCommand::Lcd { path } => {
+            let path = path
+                .or_else(|| std::env::var_os("HOME").map(PathBuf::from))
+                .ok_or_else(|| anyhow!("No directory given and $HOME is not set"))?;
+            std::env::set_current_dir(&path)
+                .with_context(|| format!("Failed to change local directory to {:?}", path))?;
+            output.print_done(serde_json::json!({ "path": std::env::current_dir()? }))?;
+        }
+        ShellCommand::Pwd => {
+            let cwd = client.current_dir().unwrap_or(home);
+            print_working_dir("Remote", cwd, output)?;
+        }
+        ShellCommand::Lpwd => {
+            let cwd = std::env::current_dir()?;
+            print_working_dir("Local", &cwd, output)?;
+        }
+        ShellCommand::Exit => return Ok(false),
+        ShellCommand::Remote(command) => {
+            // A fresh token per command: Ctrl-C only cancels the running command
+            let cancel_token = CancellationToken::new();
+            let running = execute(client, command, output, cancel_token.clone());
+            interruptible(running, &cancel_token).await?;
+        }
+    }
+    Ok(true)
+}
+
+fn print_working_dir(side: &str, cwd: &Path, output: Output) -> Result<(), anyhow::Error> {
+    if output.is_json() {
+        print_json(&serde_json::json!({ "path": cwd }))
+    } else {
+        println!("{} working directory: {}", side, cwd.display());
+        Ok(())
+    }
+}
+
+/// Lists the names of the entries of a remote directory, for tab completion
+async fn remote_names(client: &SftpClient, dir: String) -> Vec<(String, bool)> {
+    let options = WalkOptions {
+        max_depth: Some(1),
+        sort: true,
+        ..WalkOptions::default()
+    };
+    client
+        .walk(dir, options, CancellationToken::new())
+        .filter_map(|entry| async move { entry.ok() })
+        .map(|entry| {
+            let is_dir = entry.metadata.file_type == FileType::Directory;
+            (display_name(&entry.metadata), is_dir)
+        })
+        .collect()
+        .await
+}
+
+/// Line editor helper completing command names, local paths and remote paths
+struct ShellHelper {
+    commands: Vec<String>,
+    local: FilenameCompleter,
+    remote: mpsc::Sender<CompletionRequest>,
+}
+
+impl ShellHelper {
+    fn new(remote: mpsc::Sender<CompletionRequest>) -> Self {
+        let shell = ShellLine::command();
+        let mut commands: Vec<String> = shell
+            .get_subcommands()
+            .flat_map(|c| std::iter::once(c.get_name()).chain(c.get_visible_aliases()))
+            .map(str::to_string)
+            .collect();
+        commands.sort();
+        Self {
+            commands,
+            local: FilenameCompleter::new(),
+            remote,
+        }
+    }
+
+    /// Completes a remote path by listing its parent directory on the server
+    fn complete_remote(&self, word: &str) -> Vec<Pair> {
+        let (dir, prefix) = match word.rfind('/') {
+            Some(i) => (&word[..=i], &word[i + 1..]),
+            None => ("", word),
+        };
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let request = CompletionRequest {
+            dir: if dir.is_empty() { "." } else { dir }.to_string(),
+            reply: reply_tx,
+        };
+        if self.remote.blocking_send(request).is_err() {
+            return Vec::new();
+        }
+        let Ok(names) = reply_rx.blocking_recv() else {
+            return Vec::new();
+        };
+        names
+            .into_iter()
+            .filter(|(name, _)| name.starts_with(prefix))
+            .filter(|(name, _)| prefix.starts_with('.') || !name.starts_with('.'))
+            .map(|(name, is_dir)| {
+                let display = if is_dir { format!("{}/", name) } else { name };
+                Pair {
+                    replacement: format!("{}{}", dir, display),
+                    display,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Whether the argument at `position` (0-based, flags excluded) of a shell
+/// command is a local path
+fn is_local_argument(command: &str, position: usize) -> bool {
+    matches!(
+        (command, position),
+        ("lcd", _) | ("get", 1) | ("put", 0) | ("sync", 0)
+    )
+}
+
+impl Completer for ShellHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let before = &line[..pos];
+        let start = before.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+        let word = &before[start..];
+        let words: Vec<&str> = before[..start].split_whitespace().collect();
+
+        let Some((command, args)) = words.split_first() else {
+            let commands = self
+                .commands
+                .iter()
+                .filter(|c| c.starts_with(word))
+                .map(|c| Pair {
+                    display: c.clone(),
+                    replacement: format!("{} ", c),
+                })
+                .collect();
+            return Ok((start, commands));
+        };
+        let position = args.iter().filter(|arg| !arg.starts_with('-')).count();
+        if is_local_argument(command, position) {
+            self.local.complete(line, pos, ctx)
+        } else {
+            Ok((start, self.complete_remote(word)))
+        }
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
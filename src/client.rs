@@ -1,22 +1,50 @@
+use anyhow::anyhow;
+use bytes::Bytes;
+use futures::stream::Stream;
 use openssh_sftp_client::Sftp;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio_util::sync::CancellationToken;
 
-use crate::operations::{download, list, upload};
-use crate::types::{FileMetadata, FileTransferOperationResult, SftpClientConfig, SftpClientConfigArc};
+use crate::filter::FileFilter;
+use crate::operations::{
+    attributes, copy, download, du, glob, link, list, manage, memory, stat, statvfs, sync, tail,
+    transfer, upload, walk,
+};
+use crate::remote_file::RemoteFile;
+use crate::types::{
+    DiskSpace, DiskUsage, FileAttributes, FileMetadata, FileTransferOperationResult, FileType,
+    OpenOptions, SftpClientConfig, SftpClientConfigArc, SyncDirection, SyncOptions, SyncReport,
+    TailEvent, TailOptions, TransferOptions, WalkEntry, WalkOptions,
+};
 
 /// SFTP client for performing file operations on a remote server
 #[derive(Debug)]
 pub struct SftpClient {
     pub(crate) sftp: Sftp,
+    pub(crate) session: Arc<openssh::Session>,
     pub(crate) config: SftpClientConfigArc,
+    /// Remote working directory used to resolve relative paths
+    pub(crate) cwd: Option<PathBuf>,
+    /// Channel for `disk_space` requests, opened on first use
+    pub(crate) statvfs: tokio::sync::Mutex<Option<statvfs::StatvfsChannel>>,
 }
 
 impl SftpClient {
     /// Creates a new SFTP client instance (internal use)
-    pub(crate) fn new(sftp: Sftp, config: SftpClientConfig) -> Self {
+    pub(crate) fn new(
+        sftp: Sftp,
+        session: Arc<openssh::Session>,
+        config: SftpClientConfig,
+    ) -> Self {
         Self {
             sftp,
+            session,
             config: config.into(),
+            cwd: None,
+            statvfs: tokio::sync::Mutex::new(None),
         }
     }
 
@@ -33,35 +61,792 @@ impl SftpClient {
     /// ```
     pub async fn close(self) -> Result<(), anyhow::Error> {
         self.sftp.close().await?;
+        if let Some(channel) = self.statvfs.into_inner() {
+            channel.close().await?;
+        }
         Ok(())
     }
 
-    /// Lists the contents of a remote directory
+    /// Returns the remote working directory
+    ///
+    /// # Returns
+    ///
+    /// Returns `None` until `set_current_dir` is called; relative paths are
+    /// then resolved by the server, usually against the login directory
+    pub fn current_dir(&self) -> Option<&Path> {
+        self.cwd.as_deref()
+    }
+
+    /// Changes the remote working directory
+    ///
+    /// Relative paths passed to every other method of this client are
+    /// resolved against the working directory, like the `cd` command of the
+    /// OpenSSH `sftp` client. The path itself may be relative to the current
+    /// working directory and is canonicalized, so symlinks and `..` are resolved.
+    ///
+    /// # Arguments
+    ///
+    /// * `remote_dir` - Path to the new remote working directory
+    ///
+    /// # Returns
+    ///
+    /// Returns the canonical path of the new working directory
+    ///
+    /// # Errors
+    ///
+    /// Returns `SftpError::NotFound` if the directory does not exist, or an
+    /// error if the path is not a directory
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// client.set_current_dir("/var/log".to_string()).await?;
+    /// let contents = client.read_to_string("syslog".to_string()).await?;
+    /// ```
+    pub async fn set_current_dir(&mut self, remote_dir: String) -> Result<PathBuf, anyhow::Error> {
+        let canonical = self.canonicalize(remote_dir).await?;
+        let metadata = stat::stat(self, canonical.to_string_lossy().into_owned()).await?;
+        if metadata.file_type != FileType::Directory {
+            return Err(anyhow!("Not a directory: {:?}", canonical));
+        }
+        self.cwd = Some(canonical.clone());
+        Ok(canonical)
+    }
+
+    /// Resolves a remote path against the remote working directory
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Absolute path, or path relative to the working directory
+    ///
+    /// # Returns
+    ///
+    /// Returns `path` joined onto the working directory when it is relative
+    /// and a working directory is set, or `path` unchanged otherwise
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// client.set_current_dir("/data".to_string()).await?;
+    /// assert_eq!(client.resolve_path("in/a.csv".to_string()), "/data/in/a.csv");
+    /// ```
+    pub fn resolve_path(&self, path: String) -> String {
+        match &self.cwd {
+            Some(cwd) if !path.starts_with('/') => cwd.join(path).to_string_lossy().into_owned(),
+            _ => path,
+        }
+    }
+
+    /// Lists the contents of a remote directory
+    ///
+    /// # Arguments
+    ///
+    /// * `remote_dir` - Path to the remote directory
+    /// * `cancel_token` - Token for cancelling the operation
+    ///
+    /// # Returns
+    ///
+    /// Returns a vector of `FileMetadata` for files in the directory, or an
+    /// `SftpError::Cancelled` error if the listing was cancelled before completion
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let cancel_token = CancellationToken::new();
+    /// let files = client.ls("/remote/path".to_string(), cancel_token).await?;
+    /// for file in files {
+    ///     println!("{:?}: {} bytes", file.path, file.size.unwrap_or(0));
+    /// }
+    /// ```
+    pub async fn ls(
+        &self,
+        remote_dir: String,
+        cancel_token: CancellationToken,
+    ) -> Result<Vec<FileMetadata>, anyhow::Error> {
+        list::ls(self, self.resolve_path(remote_dir), cancel_token).await
+    }
+
+    /// Lists the contents of a remote directory as a stream
+    ///
+    /// Unlike `ls`, entries are yielded as the server sends them, so the first
+    /// results arrive immediately and memory usage does not grow with the
+    /// directory size. Cancelling the token ends the stream with an
+    /// `SftpError::Cancelled` error.
+    ///
+    /// # Arguments
+    ///
+    /// * `remote_dir` - Path to the remote directory
+    /// * `cancel_token` - Token for cancelling the operation
+    ///
+    /// # Returns
+    ///
+    /// Returns a stream of `FileMetadata` for files in the directory
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let files = client.ls_stream("/remote/path".to_string(), cancel_token);
+    /// futures::pin_mut!(files);
+    /// while let Some(file) = files.next().await {
+    ///     let file = file?;
+    ///     println!("{:?}: {} bytes", file.path, file.size.unwrap_or(0));
+    /// }
+    /// ```
+    pub fn ls_stream(
+        &self,
+        remote_dir: String,
+        cancel_token: CancellationToken,
+    ) -> impl Stream<Item = Result<FileMetadata, anyhow::Error>> + '_ {
+        list::ls_stream(self, self.resolve_path(remote_dir), cancel_token)
+    }
+
+    /// Lists the files of a remote directory accepted by a filter
+    ///
+    /// # Arguments
+    ///
+    /// * `remote_dir` - Path to the remote directory
+    /// * `filter` - Include/exclude rules evaluated against each file
+    /// * `cancel_token` - Token for cancelling the operation
+    ///
+    /// # Returns
+    ///
+    /// Returns a vector of `FileMetadata` for the accepted files in the directory
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let filter = FileFilter::new()
+    ///     .include("*.csv")?
+    ///     .modified_within(Duration::from_secs(24 * 60 * 60));
+    /// let files = client.ls_filtered("/data".to_string(), &filter, cancel_token).await?;
+    /// ```
+    pub async fn ls_filtered(
+        &self,
+        remote_dir: String,
+        filter: &FileFilter,
+        cancel_token: CancellationToken,
+    ) -> Result<Vec<FileMetadata>, anyhow::Error> {
+        list::ls_filtered(self, self.resolve_path(remote_dir), filter, cancel_token).await
+    }
+
+    /// Expands a glob pattern on the remote server
+    ///
+    /// Supports `*`, `?`, `[...]` and `**` wildcards, matched component by
+    /// component by walking the remote directories.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - Glob pattern, e.g. `/data/2026-*/report_*.csv`
+    /// * `cancel_token` - Token for cancelling the operation
+    ///
+    /// # Returns
+    ///
+    /// Returns a stream of `FileMetadata` for matching files and directories
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let matches = client.glob("/data/2026-*/report_*.csv".to_string(), cancel_token);
+    /// futures::pin_mut!(matches);
+    /// while let Some(file) = matches.next().await {
+    ///     println!("{:?}", file?.path);
+    /// }
+    /// ```
+    pub fn glob(
+        &self,
+        pattern: String,
+        cancel_token: CancellationToken,
+    ) -> impl Stream<Item = Result<FileMetadata, anyhow::Error>> + '_ {
+        glob::glob(self, self.resolve_path(pattern), cancel_token)
+    }
+
+    /// Recursively walks a remote directory tree
+    ///
+    /// Directories are read concurrently and entries are yielded breadth-first
+    /// in discovery order, each with its depth below `root`.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Path to the remote directory to walk
+    /// * `options` - Walk options (max depth, follow symlinks, sorting, concurrency, filter)
+    /// * `cancel_token` - Token for cancelling the walk
+    ///
+    /// # Returns
+    ///
+    /// Returns a stream of `WalkEntry` for every entry below `root`
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let options = WalkOptions { max_depth: Some(3), sort: true, ..Default::default() };
+    /// let entries = client.walk("/remote/dir".to_string(), options, cancel_token);
+    /// futures::pin_mut!(entries);
+    /// while let Some(entry) = entries.next().await {
+    ///     let entry = entry?;
+    ///     println!("{}{:?}", "  ".repeat(entry.depth - 1), entry.metadata.path);
+    /// }
+    /// ```
+    pub fn walk(
+        &self,
+        root: String,
+        options: WalkOptions,
+        cancel_token: CancellationToken,
+    ) -> impl Stream<Item = Result<WalkEntry, anyhow::Error>> + '_ {
+        walk::walk(self, self.resolve_path(root), options, cancel_token)
+    }
+
+    /// Computes the disk usage of a remote directory tree
+    ///
+    /// # Arguments
+    ///
+    /// * `remote_dir` - Path to the remote directory
+    /// * `breakdown` - Whether to also report the usage of each direct sub-directory
+    /// * `cancel_token` - Token for cancelling the operation
+    ///
+    /// # Returns
+    ///
+    /// Returns a `DiskUsage` with the total bytes, file count and directory count
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let usage = client.du("/remote/dir".to_string(), true, cancel_token).await?;
+    /// println!("{} bytes in {} files", usage.total_bytes, usage.file_count);
+    /// for (dir, sub) in usage.subdirectories.unwrap_or_default() {
+    ///     println!("  {:?}: {} bytes", dir, sub.total_bytes);
+    /// }
+    /// ```
+    pub async fn du(
+        &self,
+        remote_dir: String,
+        breakdown: bool,
+        cancel_token: CancellationToken,
+    ) -> Result<DiskUsage, anyhow::Error> {
+        du::du(self, self.resolve_path(remote_dir), breakdown, cancel_token).await
+    }
+
+    /// Queries the metadata of a single remote path, following symbolic links
+    ///
+    /// # Arguments
+    ///
+    /// * `remote_path` - Path to the remote file or directory
+    ///
+    /// # Returns
+    ///
+    /// Returns the `FileMetadata` of the path, or a `SftpError::NotFound` error
+    /// if it does not exist
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// match client.stat("/outbox/batch-42.done".to_string()).await {
+    ///     Ok(file) => println!("ready since {:?}", file.last_modified_at),
+    ///     Err(e) if matches!(e.downcast_ref(), Some(SftpError::NotFound { .. })) => {
+    ///         println!("not ready yet")
+    ///     }
+    ///     Err(e) => return Err(e),
+    /// }
+    /// ```
+    pub async fn stat(&self, remote_path: String) -> Result<FileMetadata, anyhow::Error> {
+        stat::stat(self, self.resolve_path(remote_path)).await
+    }
+
+    /// Queries the metadata of a single remote path without following symbolic links
+    ///
+    /// # Arguments
+    ///
+    /// * `remote_path` - Path to the remote file, directory or symbolic link
+    ///
+    /// # Returns
+    ///
+    /// Returns the `FileMetadata` of the path itself, with `link_target` set for
+    /// symbolic links, or a `SftpError::NotFound` error if it does not exist
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let current = client.lstat("/srv/app/current".to_string()).await?;
+    /// println!("current -> {:?}", current.link_target);
+    /// ```
+    pub async fn lstat(&self, remote_path: String) -> Result<FileMetadata, anyhow::Error> {
+        stat::lstat(self, self.resolve_path(remote_path)).await
+    }
+
+    /// Queries the free and total space of the remote filesystem
+    ///
+    /// Requires the `statvfs@openssh.com` extension, which is supported by
+    /// OpenSSH servers.
+    ///
+    /// # Arguments
+    ///
+    /// * `remote_path` - Any path on the filesystem to query
+    ///
+    /// # Returns
+    ///
+    /// Returns the `DiskSpace` of the filesystem containing `remote_path`
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let space = client.disk_space("/remote/dir".to_string()).await?;
+    /// println!("{} of {} bytes available", space.available_bytes, space.total_bytes);
+    /// ```
+    pub async fn disk_space(&self, remote_path: String) -> Result<DiskSpace, anyhow::Error> {
+        statvfs::disk_space(self, self.resolve_path(remote_path)).await
+    }
+
+    /// Creates a symbolic link on the remote server
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - Path the link points to, stored as-is (may be relative to the link)
+    /// * `link_path` - Path of the link to create, which must not exist
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// client.symlink("releases/v42".to_string(), "/srv/app/current".to_string()).await?;
+    /// ```
+    pub async fn symlink(&self, target: String, link_path: String) -> Result<(), anyhow::Error> {
+        link::symlink(self, target, self.resolve_path(link_path)).await
+    }
+
+    /// Points a symbolic link to a new target, replacing what is at `link_path`
+    ///
+    /// The new link is created under a temporary name and renamed over
+    /// `link_path`, which is atomic on servers supporting the
+    /// `posix-rename@openssh.com` extension.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - Path the link points to, stored as-is (may be relative to the link)
+    /// * `link_path` - Path of the link to create or replace
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// // Flip the "current" release after uploading a new version
+    /// client.replace_symlink("releases/v43".to_string(), "/srv/app/current".to_string()).await?;
+    /// ```
+    pub async fn replace_symlink(
+        &self,
+        target: String,
+        link_path: String,
+    ) -> Result<(), anyhow::Error> {
+        link::replace_symlink(self, target, self.resolve_path(link_path)).await
+    }
+
+    /// Reads the target of a symbolic link
+    ///
+    /// # Arguments
+    ///
+    /// * `link_path` - Path of the symbolic link
+    ///
+    /// # Returns
+    ///
+    /// Returns the target as stored in the link, without resolving it
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let release = client.read_link("/srv/app/current".to_string()).await?;
+    /// ```
+    pub async fn read_link(&self, link_path: String) -> Result<PathBuf, anyhow::Error> {
+        link::read_link(self, self.resolve_path(link_path)).await
+    }
+
+    /// Creates a hard link on the remote server
+    ///
+    /// Requires the `hardlink@openssh.com` extension, which is supported by
+    /// OpenSSH servers.
+    ///
+    /// # Arguments
+    ///
+    /// * `src_path` - Path of the existing file
+    /// * `link_path` - Path of the new link, which must not exist
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// client.hard_link("/data/export.csv".to_string(), "/outbox/export.csv".to_string()).await?;
+    /// ```
+    pub async fn hard_link(
+        &self,
+        src_path: String,
+        link_path: String,
+    ) -> Result<(), anyhow::Error> {
+        link::hard_link(
+            self,
+            self.resolve_path(src_path),
+            self.resolve_path(link_path),
+        )
+        .await
+    }
+
+    /// Resolves a remote path to its canonical absolute form (realpath)
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Remote path, which may be relative or contain symbolic links
+    ///
+    /// # Returns
+    ///
+    /// Returns the absolute path with all symbolic links, `.` and `..` resolved
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let home = client.canonicalize(".".to_string()).await?;
+    /// ```
+    pub async fn canonicalize(&self, path: String) -> Result<PathBuf, anyhow::Error> {
+        link::canonicalize(self, self.resolve_path(path)).await
+    }
+
+    /// Changes several attributes of a remote file or directory at once
+    ///
+    /// # Arguments
+    ///
+    /// * `remote_path` - Path to the remote file or directory
+    /// * `attributes` - Attributes to change; `None` fields are left unchanged
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let attributes = FileAttributes {
+    ///     permissions: Some(0o640),
+    ///     gid: Some(1002),
+    ///     ..Default::default()
+    /// };
+    /// client.set_metadata("/srv/data/report.csv".to_string(), attributes).await?;
+    /// ```
+    pub async fn set_metadata(
+        &self,
+        remote_path: String,
+        attributes: FileAttributes,
+    ) -> Result<(), anyhow::Error> {
+        attributes::set_metadata(self, self.resolve_path(remote_path), attributes).await
+    }
+
+    /// Changes the permission bits of a remote file or directory (chmod)
+    ///
+    /// # Arguments
+    ///
+    /// * `remote_path` - Path to the remote file or directory
+    /// * `mode` - Permission bits, e.g. `0o640`
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// client.set_permissions("/srv/data/report.csv".to_string(), 0o640).await?;
+    /// ```
+    pub async fn set_permissions(
+        &self,
+        remote_path: String,
+        mode: u32,
+    ) -> Result<(), anyhow::Error> {
+        attributes::set_permissions(self, self.resolve_path(remote_path), mode).await
+    }
+
+    /// Changes the numeric owner and group of a remote file or directory (chown)
+    ///
+    /// # Arguments
+    ///
+    /// * `remote_path` - Path to the remote file or directory
+    /// * `uid` - New owner user id, or `None` to keep the current one
+    /// * `gid` - New owner group id, or `None` to keep the current one
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// // Only change the group
+    /// client.set_owner("/srv/data/report.csv".to_string(), None, Some(1002)).await?;
+    /// ```
+    pub async fn set_owner(
+        &self,
+        remote_path: String,
+        uid: Option<u32>,
+        gid: Option<u32>,
+    ) -> Result<(), anyhow::Error> {
+        attributes::set_owner(self, self.resolve_path(remote_path), uid, gid).await
+    }
+
+    /// Changes the access and modification times of a remote file or directory
+    ///
+    /// # Arguments
+    ///
+    /// * `remote_path` - Path to the remote file or directory
+    /// * `accessed_at` - New access time, or `None` to keep the current one
+    /// * `modified_at` - New modification time, or `None` to keep the current one
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// client.set_times("/srv/data/report.csv".to_string(), None, Some(SystemTime::now())).await?;
+    /// ```
+    pub async fn set_times(
+        &self,
+        remote_path: String,
+        accessed_at: Option<SystemTime>,
+        modified_at: Option<SystemTime>,
+    ) -> Result<(), anyhow::Error> {
+        attributes::set_times(
+            self,
+            self.resolve_path(remote_path),
+            accessed_at,
+            modified_at,
+        )
+        .await
+    }
+
+    /// Truncates or extends a remote file to `size` bytes
+    ///
+    /// # Arguments
+    ///
+    /// * `remote_path` - Path to the remote file
+    /// * `size` - New size in bytes
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// client.truncate("/var/log/app.log".to_string(), 0).await?;
+    /// ```
+    pub async fn truncate(&self, remote_path: String, size: u64) -> Result<(), anyhow::Error> {
+        attributes::truncate(self, self.resolve_path(remote_path), size).await
+    }
+
+    /// Creates a remote directory
+    ///
+    /// # Arguments
+    ///
+    /// * `remote_dir` - Path of the directory to create; its parent must exist
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// client.create_dir("/remote/incoming".to_string()).await?;
+    /// ```
+    pub async fn create_dir(&self, remote_dir: String) -> Result<(), anyhow::Error> {
+        manage::create_dir(self, self.resolve_path(remote_dir)).await
+    }
+
+    /// Creates a remote directory and all of its missing parents, like `mkdir -p`
+    ///
+    /// # Arguments
+    ///
+    /// * `remote_dir` - Path of the directory to create
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// client.create_dir_all("/remote/archive/2026/10".to_string()).await?;
+    /// ```
+    pub async fn create_dir_all(&self, remote_dir: String) -> Result<(), anyhow::Error> {
+        manage::create_dir_all(self, self.resolve_path(remote_dir)).await
+    }
+
+    /// Removes a remote file or symbolic link
+    ///
+    /// # Arguments
+    ///
+    /// * `remote_path` - Path of the file to remove
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// client.remove_file("/remote/outbox/batch-42.csv".to_string()).await?;
+    /// ```
+    pub async fn remove_file(&self, remote_path: String) -> Result<(), anyhow::Error> {
+        manage::remove_file(self, self.resolve_path(remote_path)).await
+    }
+
+    /// Removes an empty remote directory
+    ///
+    /// # Arguments
+    ///
+    /// * `remote_dir` - Path of the directory to remove
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// client.remove_dir("/remote/incoming".to_string()).await?;
+    /// ```
+    pub async fn remove_dir(&self, remote_dir: String) -> Result<(), anyhow::Error> {
+        manage::remove_dir(self, self.resolve_path(remote_dir)).await
+    }
+
+    /// Removes a remote directory and all of its contents, like `rm -r`
+    ///
+    /// # Arguments
+    ///
+    /// * `remote_dir` - Path of the directory to remove
+    /// * `cancel_token` - Token for cancelling the removal
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// client.remove_dir_all("/remote/tmp/build-1234".to_string(), cancel_token).await?;
+    /// ```
+    pub async fn remove_dir_all(
+        &self,
+        remote_dir: String,
+        cancel_token: CancellationToken,
+    ) -> Result<(), anyhow::Error> {
+        manage::remove_dir_all(self, self.resolve_path(remote_dir), cancel_token).await
+    }
+
+    /// Renames or moves a remote file or directory
     ///
     /// # Arguments
     ///
-    /// * `remote_dir` - Path to the remote directory
-    /// * `cancel_token` - Token for cancelling the operation
+    /// * `src_path` - Current path
+    /// * `dest_path` - New path, replaced atomically on servers supporting
+    ///   the `posix-rename@openssh.com` extension
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// client.rename("/remote/upload.tmp".to_string(), "/remote/upload.csv".to_string()).await?;
+    /// ```
+    pub async fn rename(&self, src_path: String, dest_path: String) -> Result<(), anyhow::Error> {
+        manage::rename(
+            self,
+            self.resolve_path(src_path),
+            self.resolve_path(dest_path),
+        )
+        .await
+    }
+
+    /// Opens a remote file for random access
+    ///
+    /// The returned `RemoteFile` implements `AsyncRead`, `AsyncWrite` and
+    /// `AsyncSeek`, so parts of a file can be read or patched without
+    /// transferring the whole file.
+    ///
+    /// # Arguments
+    ///
+    /// * `remote_path` - Path to the remote file
+    /// * `options` - Access mode and creation flags
     ///
     /// # Returns
     ///
-    /// Returns a vector of `FileMetadata` for files in the directory
+    /// Returns a `RemoteFile` handle positioned at the start of the file, or at
+    /// the end of the file when `options.append` is set
     ///
     /// # Example
     ///
     /// ```ignore
-    /// let cancel_token = CancellationToken::new();
-    /// let files = client.ls("/remote/path".to_string(), cancel_token).await?;
-    /// for file in files {
-    ///     println!("{:?}: {} bytes", file.path, file.size.unwrap_or(0));
+    /// use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    ///
+    /// // Read the first 512 bytes of a large file
+    /// let options = OpenOptions { read: true, ..Default::default() };
+    /// let mut file = client.open("/remote/data.bin".to_string(), options).await?;
+    /// let mut header = vec![0; 512];
+    /// file.read_exact(&mut header).await?;
+    ///
+    /// // Append a line to a log file
+    /// let options = OpenOptions { append: true, create: true, ..Default::default() };
+    /// let mut log = client.open("/remote/app.log".to_string(), options).await?;
+    /// log.write_all(b"deployed\n").await?;
+    /// log.close().await?;
+    /// ```
+    pub async fn open(
+        &self,
+        remote_path: String,
+        options: OpenOptions,
+    ) -> Result<RemoteFile, anyhow::Error> {
+        RemoteFile::open(self, self.resolve_path(remote_path), options).await
+    }
+
+    /// Follows a growing remote file, like `tail -f`
+    ///
+    /// # Arguments
+    ///
+    /// * `remote_path` - Path to the remote file
+    /// * `options` - Poll interval and starting position
+    /// * `cancel_token` - Token for stopping the tail
+    ///
+    /// # Returns
+    ///
+    /// Returns a stream of `TailEvent`: appended data, and notifications when
+    /// the file is truncated or rotated
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use futures::TryStreamExt;
+    ///
+    /// let path = "/var/log/app.log".to_string();
+    /// let mut events = client.tail(path, TailOptions::default(), cancel_token);
+    /// while let Some(event) = events.try_next().await? {
+    ///     match event {
+    ///         TailEvent::Data(bytes) => print!("{}", String::from_utf8_lossy(&bytes)),
+    ///         TailEvent::Truncated | TailEvent::Rotated => println!("--- log restarted ---"),
+    ///     }
     /// }
     /// ```
-    pub async fn ls(
+    pub fn tail(
         &self,
-        remote_dir: String,
+        remote_path: String,
+        options: TailOptions,
         cancel_token: CancellationToken,
-    ) -> Result<Vec<FileMetadata>, anyhow::Error> {
-        list::ls(self, remote_dir, cancel_token).await
+    ) -> impl Stream<Item = Result<TailEvent, anyhow::Error>> + '_ {
+        tail::tail(self, self.resolve_path(remote_path), options, cancel_token)
+    }
+
+    /// Reads a whole remote file into memory
+    ///
+    /// # Arguments
+    ///
+    /// * `remote_path` - Path to the remote file
+    ///
+    /// # Returns
+    ///
+    /// Returns the contents of the file. Files larger than `max_in_memory_size`
+    /// are rejected with `SftpError::FileTooLarge`.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let contents = client.read_to_bytes("/remote/state.bin".to_string()).await?;
+    /// ```
+    pub async fn read_to_bytes(&self, remote_path: String) -> Result<Vec<u8>, anyhow::Error> {
+        memory::read_to_bytes(self, self.resolve_path(remote_path)).await
+    }
+
+    /// Reads a whole remote UTF-8 text file into memory
+    ///
+    /// # Arguments
+    ///
+    /// * `remote_path` - Path to the remote file
+    ///
+    /// # Returns
+    ///
+    /// Returns the contents of the file. Files larger than `max_in_memory_size`
+    /// are rejected with `SftpError::FileTooLarge`.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let config = client.read_to_string("/remote/app.toml".to_string()).await?;
+    /// ```
+    pub async fn read_to_string(&self, remote_path: String) -> Result<String, anyhow::Error> {
+        memory::read_to_string(self, self.resolve_path(remote_path)).await
+    }
+
+    /// Writes a buffer to a remote file, creating or truncating it
+    ///
+    /// # Arguments
+    ///
+    /// * `remote_path` - Destination path on the remote server
+    /// * `data` - Contents of the file
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// client.write_bytes("/remote/batch-42/.done".to_string(), b"ok\n").await?;
+    /// ```
+    pub async fn write_bytes(&self, remote_path: String, data: &[u8]) -> Result<(), anyhow::Error> {
+        memory::write_bytes(self, self.resolve_path(remote_path), data).await
     }
 
     /// Uploads a local file to the remote server
@@ -92,7 +877,54 @@ impl SftpClient {
         remote_path: String,
         cancel_token: CancellationToken,
     ) -> Result<FileTransferOperationResult, anyhow::Error> {
-        upload::put(self, local_path, remote_path, cancel_token).await
+        upload::put(
+            self,
+            local_path,
+            self.resolve_path(remote_path),
+            cancel_token,
+        )
+        .await
+    }
+
+    /// Uploads data read from an `AsyncRead` source to the remote server
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Source of the data, read until end of file
+    /// * `remote_path` - Destination path on the remote server
+    /// * `size_hint` - Expected number of bytes, used for the disk space check
+    /// * `cancel_token` - Token for cancelling the upload
+    ///
+    /// # Returns
+    ///
+    /// Returns a `FileTransferOperationResult` indicating completion or cancellation
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let report = generate_report().await?;
+    /// let result = client.put_from_reader(
+    ///     report.as_bytes(),
+    ///     "/remote/report.csv".to_string(),
+    ///     Some(report.len() as u64),
+    ///     CancellationToken::new(),
+    /// ).await?;
+    /// ```
+    pub async fn put_from_reader(
+        &self,
+        reader: impl AsyncRead,
+        remote_path: String,
+        size_hint: Option<u64>,
+        cancel_token: CancellationToken,
+    ) -> Result<FileTransferOperationResult, anyhow::Error> {
+        upload::put_from_reader(
+            self,
+            reader,
+            self.resolve_path(remote_path),
+            size_hint,
+            cancel_token,
+        )
+        .await
     }
 
     /// Downloads a file from the remote server to local storage
@@ -123,6 +955,315 @@ impl SftpClient {
         local_path: String,
         cancel_token: CancellationToken,
     ) -> Result<FileTransferOperationResult, anyhow::Error> {
-        download::get(self, remote_path, local_path, cancel_token).await
+        download::get(
+            self,
+            self.resolve_path(remote_path),
+            local_path,
+            cancel_token,
+        )
+        .await
+    }
+
+    /// Downloads a remote file into an `AsyncWrite` sink
+    ///
+    /// # Arguments
+    ///
+    /// * `remote_path` - Path to the remote file
+    /// * `writer` - Destination of the file contents, flushed on completion
+    /// * `cancel_token` - Token for cancelling the download
+    ///
+    /// # Returns
+    ///
+    /// Returns a `FileTransferOperationResult` indicating completion or cancellation
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let mut contents = Vec::new();
+    /// let result = client.get_to_writer(
+    ///     "/remote/data.json".to_string(),
+    ///     &mut contents,
+    ///     CancellationToken::new(),
+    /// ).await?;
+    /// ```
+    pub async fn get_to_writer(
+        &self,
+        remote_path: String,
+        writer: impl AsyncWrite,
+        cancel_token: CancellationToken,
+    ) -> Result<FileTransferOperationResult, anyhow::Error> {
+        download::get_to_writer(self, self.resolve_path(remote_path), writer, cancel_token).await
+    }
+
+    /// Downloads a byte range of a remote file to local storage
+    ///
+    /// # Arguments
+    ///
+    /// * `remote_path` - Path to the remote file
+    /// * `local_path` - Local destination path
+    /// * `offset` - Position of the first byte to download
+    /// * `len` - Maximum number of bytes to download, truncated at the end of the file
+    /// * `cancel_token` - Token for cancelling the download
+    ///
+    /// # Returns
+    ///
+    /// Returns a `FileTransferOperationResult` indicating completion or cancellation
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// // Download the fourth 1MB block of a large binary
+    /// let result = client.get_range(
+    ///     "/remote/disk.img".to_string(),
+    ///     "/local/block-3.bin".to_string(),
+    ///     3 * 1024 * 1024,
+    ///     1024 * 1024,
+    ///     cancel_token,
+    /// ).await?;
+    /// ```
+    pub async fn get_range(
+        &self,
+        remote_path: String,
+        local_path: String,
+        offset: u64,
+        len: u64,
+        cancel_token: CancellationToken,
+    ) -> Result<FileTransferOperationResult, anyhow::Error> {
+        download::get_range(
+            self,
+            self.resolve_path(remote_path),
+            local_path,
+            offset,
+            len,
+            cancel_token,
+        )
+        .await
+    }
+
+    /// Downloads a byte range of a remote file into an `AsyncWrite` sink
+    ///
+    /// # Arguments
+    ///
+    /// * `remote_path` - Path to the remote file
+    /// * `writer` - Destination of the range contents, flushed on completion
+    /// * `offset` - Position of the first byte to download
+    /// * `len` - Maximum number of bytes to download, truncated at the end of the file
+    /// * `cancel_token` - Token for cancelling the download
+    ///
+    /// # Returns
+    ///
+    /// Returns a `FileTransferOperationResult` indicating completion or cancellation
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// // Fetch the last 64KB of a log whose `size` is known, e.g. from `ls`
+    /// let mut tail = Vec::new();
+    /// client.get_range_to_writer(
+    ///     "/remote/app.log".to_string(),
+    ///     &mut tail,
+    ///     size.saturating_sub(65_536),
+    ///     65_536,
+    ///     cancel_token,
+    /// ).await?;
+    /// ```
+    pub async fn get_range_to_writer(
+        &self,
+        remote_path: String,
+        writer: impl AsyncWrite,
+        offset: u64,
+        len: u64,
+        cancel_token: CancellationToken,
+    ) -> Result<FileTransferOperationResult, anyhow::Error> {
+        download::get_range_to_writer(
+            self,
+            self.resolve_path(remote_path),
+            writer,
+            offset,
+            len,
+            cancel_token,
+        )
+        .await
+    }
+
+    /// Downloads a remote file as a stream of byte chunks
+    ///
+    /// # Arguments
+    ///
+    /// * `remote_path` - Path to the remote file
+    /// * `cancel_token` - Token for cancelling the download
+    ///
+    /// # Returns
+    ///
+    /// Returns a stream yielding the file contents in order, in chunks of up to
+    /// `io_size` bytes
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use futures::TryStreamExt;
+    ///
+    /// let mut hasher = Sha256::new();
+    /// let mut chunks = client.get_stream("/remote/image.iso".to_string(), cancel_token);
+    /// while let Some(chunk) = chunks.try_next().await? {
+    ///     hasher.update(&chunk);
+    /// }
+    /// ```
+    pub fn get_stream(
+        &self,
+        remote_path: String,
+        cancel_token: CancellationToken,
+    ) -> impl Stream<Item = Result<Bytes, anyhow::Error>> + '_ {
+        download::get_stream(self, self.resolve_path(remote_path), cancel_token)
+    }
+
+    /// Copies a file to another path on the same remote server
+    ///
+    /// Uses the server-side `copy-data` extension when available, so no file
+    /// contents cross the network, and falls back to streaming the file
+    /// through the client otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `src_path` - Path of the remote file to copy
+    /// * `dest_path` - Destination path on the same server
+    /// * `cancel_token` - Token for cancelling the copy
+    ///
+    /// # Returns
+    ///
+    /// Returns a `FileTransferOperationResult` indicating completion or cancellation
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let result = client.copy(
+    ///     "/data/export.parquet".to_string(),
+    ///     "/archive/2026-10-18/export.parquet".to_string(),
+    ///     cancel_token,
+    /// ).await?;
+    /// ```
+    pub async fn copy(
+        &self,
+        src_path: String,
+        dest_path: String,
+        cancel_token: CancellationToken,
+    ) -> Result<FileTransferOperationResult, anyhow::Error> {
+        copy::copy(
+            self,
+            self.resolve_path(src_path),
+            self.resolve_path(dest_path),
+            cancel_token,
+        )
+        .await
+    }
+
+    /// Transfers a file from this client's server to another server
+    ///
+    /// The file is streamed between the two sessions without touching local
+    /// disk, optionally resuming a partial copy and verifying checksums.
+    ///
+    /// # Arguments
+    ///
+    /// * `src_path` - Path of the file on this client's server
+    /// * `dest_client` - Client connected to the destination server
+    /// * `dest_path` - Destination path on the destination server
+    /// * `options` - Resume, checksum and progress options
+    /// * `cancel_token` - Token for cancelling the transfer
+    ///
+    /// # Returns
+    ///
+    /// Returns a `FileTransferOperationResult` indicating completion or cancellation
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let (progress_tx, mut progress_rx) = tokio::sync::mpsc::channel(16);
+    /// tokio::spawn(async move {
+    ///     while let Some(progress) = progress_rx.recv().await {
+    ///         println!("{:.1}%", progress.percentage_progress);
+    ///     }
+    /// });
+    /// let options = TransferOptions {
+    ///     resume: true,
+    ///     verify_checksum: true,
+    ///     progress: Some(progress_tx),
+    /// };
+    /// let result = server_a.transfer_to(
+    ///     "/exports/data.tar".to_string(),
+    ///     &server_b,
+    ///     "/imports/data.tar".to_string(),
+    ///     options,
+    ///     cancel_token,
+    /// ).await?;
+    /// ```
+    pub async fn transfer_to(
+        &self,
+        src_path: String,
+        dest_client: &SftpClient,
+        dest_path: String,
+        options: TransferOptions,
+        cancel_token: CancellationToken,
+    ) -> Result<FileTransferOperationResult, anyhow::Error> {
+        transfer::transfer(
+            self,
+            self.resolve_path(src_path),
+            dest_client,
+            dest_client.resolve_path(dest_path),
+            options,
+            cancel_token,
+        )
+        .await
+    }
+
+    /// Mirrors a directory tree between local storage and the remote server
+    ///
+    /// Files are compared by size and modification time (or by checksum when
+    /// `options.checksum` is set) and only new or changed files are transferred.
+    ///
+    /// # Arguments
+    ///
+    /// * `local_dir` - Path to the local directory
+    /// * `remote_dir` - Path to the remote directory
+    /// * `direction` - `SyncDirection::Upload` mirrors local to remote,
+    ///   `SyncDirection::Download` mirrors remote to local
+    /// * `options` - Sync options (delete extraneous files, dry run, checksum)
+    /// * `cancel_token` - Token for cancelling the sync
+    ///
+    /// # Returns
+    ///
+    /// Returns a `SyncReport` listing the performed (or, on dry run, planned) actions
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let cancel_token = CancellationToken::new();
+    /// let report = client.sync(
+    ///     "/local/dir".to_string(),
+    ///     "/remote/dir".to_string(),
+    ///     SyncDirection::Upload,
+    ///     SyncOptions { delete: true, dry_run: true, ..Default::default() },
+    ///     cancel_token,
+    /// ).await?;
+    /// for action in &report.actions {
+    ///     println!("{}", action);
+    /// }
+    /// ```
+    pub async fn sync(
+        &self,
+        local_dir: String,
+        remote_dir: String,
+        direction: SyncDirection,
+        options: SyncOptions,
+        cancel_token: CancellationToken,
+    ) -> Result<SyncReport, anyhow::Error> {
+        sync::sync(
+            self,
+            local_dir,
+            self.resolve_path(remote_dir),
+            direction,
+            options,
+            cancel_token,
+        )
+        .await
     }
 }
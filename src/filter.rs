@@ -0,0 +1,333 @@
+use anyhow::anyhow;
+use globset::{GlobBuilder, GlobMatcher};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use crate::types::{FileMetadata, FileType};
+
+/// Include/exclude rules evaluated against `FileMetadata`
+///
+/// Patterns follow gitignore conventions:
+/// - A pattern without a `/` (e.g. `*.csv`) matches the file name at any depth
+/// - A pattern containing a `/` (e.g. `/logs/*.log` or `logs/**/*.gz`) is
+///   anchored at the root of the listed or traversed tree
+/// - A trailing `/` (e.g. `target/`) only matches directories
+/// - A leading `!` in an exclude pattern re-includes paths excluded by an
+///   earlier exclude pattern; the last matching exclude pattern wins
+///
+/// Include patterns, size ranges and modification time ranges only apply to
+/// regular files. Directories are only filtered by exclude patterns and the
+/// hidden-file toggle, so recursive operations still descend into them.
+///
+/// Filters apply to `ls_filtered`, `walk` (`WalkOptions::filter`) and `sync`
+/// (`SyncOptions::filter`). `put` and `get` transfer single files, so
+/// directory trees are uploaded or downloaded selectively with `sync`.
+///
+/// # Example
+///
+/// ```ignore
+/// // Only CSV files modified in the last 24 hours
+/// let filter = FileFilter::new()
+///     .include("*.csv")?
+///     .modified_within(Duration::from_secs(24 * 60 * 60));
+/// let files = client.ls_filtered("/data".to_string(), &filter, cancel_token).await?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct FileFilter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+    modified_after: Option<SystemTime>,
+    modified_before: Option<SystemTime>,
+    include_hidden: bool,
+}
+
+/// Compiled gitignore-style glob pattern
+#[derive(Debug, Clone)]
+struct Pattern {
+    matcher: GlobMatcher,
+    negated: bool,
+    dir_only: bool,
+}
+
+impl Pattern {
+    fn new(pattern: &str) -> Result<Self, anyhow::Error> {
+        let (negated, pattern) = match pattern.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, pattern),
+        };
+        let (dir_only, pattern) = match pattern.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, pattern),
+        };
+        if pattern.is_empty() {
+            return Err(anyhow!("Empty filter pattern"));
+        }
+        let glob = match pattern.strip_prefix('/') {
+            Some(anchored) => anchored.to_string(),
+            None if pattern.contains('/') => pattern.to_string(),
+            None => format!("**/{}", pattern),
+        };
+        let matcher = GlobBuilder::new(&glob)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| anyhow!("Invalid filter pattern {:?}: {}", pattern, e))?
+            .compile_matcher();
+        Ok(Self {
+            matcher,
+            negated,
+            dir_only,
+        })
+    }
+
+    fn is_match(&self, relative: &Path, is_dir: bool) -> bool {
+        (!self.dir_only || is_dir) && self.matcher.is_match(relative)
+    }
+}
+
+impl Default for FileFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FileFilter {
+    /// Creates a filter which matches every file
+    pub fn new() -> Self {
+        Self {
+            include: Vec::new(),
+            exclude: Vec::new(),
+            min_size: None,
+            max_size: None,
+            modified_after: None,
+            modified_before: None,
+            include_hidden: true,
+        }
+    }
+
+    /// Adds an include pattern. When at least one include pattern is set,
+    /// only files matching one of them are accepted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is not a valid glob
+    pub fn include(mut self, pattern: &str) -> Result<Self, anyhow::Error> {
+        self.include.push(Pattern::new(pattern)?);
+        Ok(self)
+    }
+
+    /// Adds an exclude pattern. Excluded directories are not descended into
+    /// by recursive operations.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is not a valid glob
+    pub fn exclude(mut self, pattern: &str) -> Result<Self, anyhow::Error> {
+        self.exclude.push(Pattern::new(pattern)?);
+        Ok(self)
+    }
+
+    /// Only accepts files of at least `bytes` bytes
+    pub fn min_size(mut self, bytes: u64) -> Self {
+        self.min_size = Some(bytes);
+        self
+    }
+
+    /// Only accepts files of at most `bytes` bytes
+    pub fn max_size(mut self, bytes: u64) -> Self {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    /// Only accepts files modified at or after `time`
+    pub fn modified_after(mut self, time: SystemTime) -> Self {
+        self.modified_after = Some(time);
+        self
+    }
+
+    /// Only accepts files modified at or before `time`
+    pub fn modified_before(mut self, time: SystemTime) -> Self {
+        self.modified_before = Some(time);
+        self
+    }
+
+    /// Only accepts files modified within `duration` from now
+    pub fn modified_within(self, duration: Duration) -> Self {
+        let now = SystemTime::now();
+        self.modified_after(now.checked_sub(duration).unwrap_or(SystemTime::UNIX_EPOCH))
+    }
+
+    /// Whether entries whose name starts with `.` are accepted (default: `true`)
+    pub fn include_hidden(mut self, include_hidden: bool) -> Self {
+        self.include_hidden = include_hidden;
+        self
+    }
+
+    /// Checks whether a file is accepted by the filter, matching path
+    /// patterns against the file name only
+    pub fn matches(&self, file: &FileMetadata) -> bool {
+        match file.path.file_name() {
+            Some(name) => self.matches_relative(file, Path::new(name)),
+            None => self.matches_relative(file, &file.path),
+        }
+    }
+
+    /// Checks whether a file is accepted by the filter, matching path
+    /// patterns against `relative`, the path of the file relative to the
+    /// root of the listed or traversed tree
+    pub fn matches_relative(&self, file: &FileMetadata, relative: &Path) -> bool {
+        let is_dir = file.file_type == FileType::Directory;
+        if !self.allows_path(relative, is_dir) {
+            return false;
+        }
+        if is_dir {
+            return true;
+        }
+
+        if !self.include.is_empty() && !self.include.iter().any(|p| p.is_match(relative, false)) {
+            return false;
+        }
+        if let Some(size) = file.size {
+            if self.min_size.is_some_and(|min| size < min)
+                || self.max_size.is_some_and(|max| size > max)
+            {
+                return false;
+            }
+        } else if self.min_size.is_some() || self.max_size.is_some() {
+            return false;
+        }
+        if let Some(modified) = file.last_modified_at {
+            if self.modified_after.is_some_and(|after| modified < after)
+                || self.modified_before.is_some_and(|before| modified > before)
+            {
+                return false;
+            }
+        } else if self.modified_after.is_some() || self.modified_before.is_some() {
+            return false;
+        }
+        true
+    }
+
+    /// Applies the hidden-file toggle and the exclude patterns to a path
+    fn allows_path(&self, relative: &Path, is_dir: bool) -> bool {
+        let hidden = relative
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if hidden && !self.include_hidden {
+            return false;
+        }
+        let excluded = self
+            .exclude
+            .iter()
+            .rev()
+            .find(|p| p.is_match(relative, is_dir))
+            .is_some_and(|p| !p.negated);
+        !excluded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn entry(path: &str, file_type: FileType) -> FileMetadata {
+        FileMetadata {
+            path: PathBuf::from(path),
+            size: Some(10),
+            file_type,
+            last_accessed_at: None,
+            last_modified_at: None,
+            permissions: None,
+            uid: None,
+            gid: None,
+            link_target: None,
+        }
+    }
+
+    fn accepts(filter: &FileFilter, relative: &str, file_type: FileType) -> bool {
+        let file = entry(&format!("/root/{}", relative), file_type);
+        filter.matches_relative(&file, Path::new(relative))
+    }
+
+    #[test]
+    fn unanchored_pattern_matches_name_at_any_depth() {
+        let filter = FileFilter::new().exclude("*.csv").unwrap();
+        assert!(!accepts(&filter, "a.csv", FileType::Regular));
+        assert!(!accepts(&filter, "x/y/a.csv", FileType::Regular));
+        assert!(accepts(&filter, "x/a.csv.gz", FileType::Regular));
+    }
+
+    #[test]
+    fn anchored_pattern_matches_from_the_root() {
+        let filter = FileFilter::new().exclude("/logs/*.log").unwrap();
+        assert!(!accepts(&filter, "logs/a.log", FileType::Regular));
+        assert!(accepts(&filter, "x/logs/a.log", FileType::Regular));
+        assert!(accepts(&filter, "logs/old/a.log", FileType::Regular));
+
+        let filter = FileFilter::new().exclude("logs/**/*.gz").unwrap();
+        assert!(!accepts(&filter, "logs/a.gz", FileType::Regular));
+        assert!(!accepts(&filter, "logs/2026/10/a.gz", FileType::Regular));
+        assert!(accepts(&filter, "x/logs/a.gz", FileType::Regular));
+    }
+
+    #[test]
+    fn directory_only_pattern_skips_files() {
+        let filter = FileFilter::new().exclude("target/").unwrap();
+        assert!(!accepts(&filter, "target", FileType::Directory));
+        assert!(!accepts(&filter, "crate/target", FileType::Directory));
+        assert!(accepts(&filter, "target", FileType::Regular));
+    }
+
+    #[test]
+    fn exclude_takes_precedence_over_include() {
+        let filter = FileFilter::new()
+            .include("*.csv")
+            .unwrap()
+            .exclude("secret*")
+            .unwrap();
+        assert!(accepts(&filter, "report.csv", FileType::Regular));
+        assert!(!accepts(&filter, "secret.csv", FileType::Regular));
+        assert!(!accepts(&filter, "report.txt", FileType::Regular));
+    }
+
+    #[test]
+    fn include_patterns_do_not_filter_directories() {
+        let filter = FileFilter::new().include("*.csv").unwrap();
+        assert!(accepts(&filter, "data", FileType::Directory));
+        assert!(accepts(&filter, "data/a.csv", FileType::Regular));
+    }
+
+    #[test]
+    fn last_matching_exclude_pattern_wins() {
+        let filter = FileFilter::new()
+            .exclude("*.log")
+            .unwrap()
+            .exclude("!keep.log")
+            .unwrap();
+        assert!(!accepts(&filter, "debug.log", FileType::Regular));
+        assert!(accepts(&filter, "keep.log", FileType::Regular));
+
+        let filter = FileFilter::new()
+            .exclude("!keep.log")
+            .unwrap()
+            .exclude("*.log")
+            .unwrap();
+        assert!(!accepts(&filter, "keep.log", FileType::Regular));
+    }
+
+    #[test]
+    fn hidden_entries_are_skipped_when_disabled() {
+        let filter = FileFilter::new().include_hidden(false);
+        assert!(!accepts(&filter, "a/.env", FileType::Regular));
+        assert!(!accepts(&filter, ".git", FileType::Directory));
+        assert!(accepts(&filter, "a/env", FileType::Regular));
+    }
+
+    #[test]
+    fn rejects_empty_patterns() {
+        assert!(FileFilter::new().exclude("!").is_err());
+        assert!(FileFilter::new().include("/").is_err());
+    }
+}
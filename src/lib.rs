@@ -1,15 +1,25 @@
 // Module declarations
 mod client;
+mod filter;
 mod operations;
+mod pool;
+mod remote_file;
 mod session;
 mod types;
 mod utils;
 
 // Public API exports
 pub use client::SftpClient;
+pub use filter::FileFilter;
+pub use pool::{PooledClient, SessionPool};
+pub use remote_file::RemoteFile;
 pub use session::SftpSessionManager;
 pub use types::{
-    FileMetadata, FileTransferOperationResult, FileTransferProgress, FileType, SftpClientConfig,
+    ConnectOptions, DiskSpace, DiskUsage, FileAttributes, FileMetadata,
+    FileTransferOperationResult, FileTransferProgress, FileType, KnownHostsPolicy, OpenOptions,
+    SessionKey, SessionPoolConfig, SftpClientConfig, SftpError, SyncAction, SyncDirection,
+    SyncOptions, SyncReason, SyncReport, TailEvent, TailOptions, TransferOptions, WalkEntry,
+    WalkOptions,
 };
 
 // Re-export commonly used external types for convenience
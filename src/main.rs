@@ -1,3 +1,43 @@
-fn main() {
-    println!("Hello, world!");
+mod cli;
+
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
+use std::process::ExitCode;
+use tracing::Level;
+
+use crate::cli::args::Cli;
+use crate::cli::commands;
+use crate::cli::format::Output;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    if cli.batch.is_some() && cli.command.is_some() {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--batch cannot be used with a command",
+            )
+            .exit();
+    }
+
+    let level = match cli.verbose {
+        0 => Level::WARN,
+        1 => Level::INFO,
+        2 => Level::DEBUG,
+        _ => Level::TRACE,
+    };
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(std::io::stderr)
+        .init();
+
+    let output = Output::from(&cli);
+    match commands::run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            output.print_error(&e);
+            ExitCode::from(commands::exit_code(&e))
+        }
+    }
 }
@@ -0,0 +1,306 @@
+use anyhow::anyhow;
+use openssh_sftp_client::UnixTimeStamp;
+use openssh_sftp_client::metadata::{MetaData, MetaDataBuilder, Permissions};
+use std::time::SystemTime;
+use tracing::info;
+
+use crate::client::SftpClient;
+use crate::types::FileAttributes;
+
+/// Changes the attributes of a remote file or directory (setstat)
+///
+/// SFTP sets the owner ids and the timestamps in pairs, so when only one of
+/// `uid`/`gid` or `accessed_at`/`modified_at` is given, the other one is
+/// read from the current attributes of the path first.
+///
+/// # Arguments
+///
+/// * `client` - The SFTP client instance
+/// * `remote_path` - Path to the remote file or directory
+/// * `attributes` - Attributes to change; `None` fields are left unchanged
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The current attributes are needed and cannot be read
+/// - A timestamp is outside the range supported by SFTP
+/// - The server refuses the change (e.g. `chown` without privileges)
+pub async fn set_metadata(
+    client: &SftpClient,
+    remote_path: String,
+    attributes: FileAttributes,
+) -> Result<(), anyhow::Error> {
+    let current = if needs_current(&attributes) {
+        Some(client.sftp.fs().metadata(&remote_path).await?)
+    } else {
+        None
+    };
+    let metadata = to_metadata(&attributes, current.as_ref())?;
+    client
+        .sftp
+        .fs()
+        .set_metadata(&remote_path, metadata)
+        .await
+        .map_err(|e| anyhow!("Failed to set attributes of {:?}: {}", remote_path, e))?;
+    info!("Attributes of {:?} set: {:?}", remote_path, attributes);
+    Ok(())
+}
+
+/// Changes the permission bits of a remote file or directory (chmod)
+///
+/// # Errors
+///
+/// Returns the same errors as [`set_metadata`]
+pub async fn set_permissions(
+    client: &SftpClient,
+    remote_path: String,
+    mode: u32,
+) -> Result<(), anyhow::Error> {
+    let attributes = FileAttributes {
+        permissions: Some(mode),
+        ..FileAttributes::default()
+    };
+    set_metadata(client, remote_path, attributes).await
+}
+
+/// Changes the numeric owner and group of a remote file or directory (chown)
+///
+/// # Errors
+///
+/// Returns the same errors as [`set_metadata`]
+pub async fn set_owner(
+    client: &SftpClient,
+    remote_path: String,
+    uid: Option<u32>,
+    gid: Option<u32>,
+) -> Result<(), anyhow::Error> {
+    let attributes = FileAttributes {
+        uid,
+        gid,
+        ..FileAttributes::default()
+    };
+    set_metadata(client, remote_path, attributes).await
+}
+
+/// Changes the access and modification times of a remote file or directory (utimes)
+///
+/// # Errors
+///
+/// Returns the same errors as [`set_metadata`]
+pub async fn set_times(
+    client: &SftpClient,
+    remote_path: String,
+    accessed_at: Option<SystemTime>,
+    modified_at: Option<SystemTime>,
+) -> Result<(), anyhow::Error> {
+    let attributes = FileAttributes {
+        accessed_at,
+        modified_at,
+        ..FileAttributes::default()
+    };
+    set_metadata(client, remote_path, attributes).await
+}
+
+/// Truncates or extends a remote file to `size` bytes
+///
+/// # Errors
+///
+/// Returns the same errors as [`set_metadata`]
+pub async fn truncate(
+    client: &SftpClient,
+    remote_path: String,
+    size: u64,
+) -> Result<(), anyhow::Error> {
+    let attributes = FileAttributes {
+        size: Some(size),
+        ..FileAttributes::default()
+    };
+    set_metadata(client, remote_path, attributes).await
+}
+
+/// Whether only half of a pair of attributes SFTP sets together is given
+pub(crate) fn needs_current(attributes: &FileAttributes) -> bool {
+    attributes.uid.is_some() != attributes.gid.is_some()
+        || attributes.accessed_at.is_some() != attributes.modified_at.is_some()
+}
+
+/// Converts the attributes to change into SFTP attributes, completing
+/// partial pairs from `current`
+pub(crate) fn to_metadata(
+    attributes: &FileAttributes,
+    current: Option<&MetaData>,
+) -> Result<MetaData, anyhow::Error> {
+    let mut builder = MetaDataBuilder::new();
+    if let Some(mode) = attributes.permissions {
+        builder.permissions(permissions(mode));
+    }
+    if let Some(size) = attributes.size {
+        builder.len(size);
+    }
+    if attributes.uid.is_some() || attributes.gid.is_some() {
+        let uid = attributes.uid.or_else(|| current.and_then(|c| c.uid()));
+        let gid = attributes.gid.or_else(|| current.and_then(|c| c.gid()));
+        let (Some(uid), Some(gid)) = (uid, gid) else {
+            return Err(anyhow!("Server did not report the current owner"));
+        };
+        builder.id((uid, gid));
+    }
+    if attributes.accessed_at.is_some() || attributes.modified_at.is_some() {
+        let current_time = |time: Option<UnixTimeStamp>| time.map(|t| t.as_system_time());
+        let accessed = attributes
+            .accessed_at
+            .or_else(|| current.and_then(|c| current_time(c.accessed())));
+        let modified = attributes
+            .modified_at
+            .or_else(|| current.and_then(|c| current_time(c.modified())));
+        let (Some(accessed), Some(modified)) = (accessed, modified) else {
+            return Err(anyhow!("Server did not report the current timestamps"));
+        };
+        builder.time(UnixTimeStamp::new(accessed)?, UnixTimeStamp::new(modified)?);
+    }
+    Ok(builder.create())
+}
+
+/// Converts numeric mode bits into SFTP permissions
+fn permissions(mode: u32) -> Permissions {
+    let mut permissions = Permissions::from((mode & 0o7777) as u16);
+    // `Permissions::from` maps the setuid bit onto setgid, set both explicitly
+    permissions.set_suid(mode & 0o4000 != 0);
+    permissions.set_sgid(mode & 0o2000 != 0);
+    permissions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::mode;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn time(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    fn current() -> MetaData {
+        let mut builder = MetaDataBuilder::new();
+        builder.id((1000, 100));
+        builder.time(
+            UnixTimeStamp::new(time(1_000)).unwrap(),
+            UnixTimeStamp::new(time(2_000)).unwrap(),
+        );
+        builder.create()
+    }
+
+    fn seconds(time: Option<UnixTimeStamp>) -> Option<u64> {
+        time.map(|t| t.as_duration().as_secs())
+    }
+
+    #[test]
+    fn permissions_round_trip_special_bits() {
+        for bits in [0o4755, 0o2755, 0o1777, 0o644, 0o7000, 0o6711] {
+            assert_eq!(mode(permissions(bits)), bits, "{:o}", bits);
+        }
+        // Only the permission bits are sent
+        assert_eq!(mode(permissions(0o100644)), 0o644);
+    }
+
+    #[test]
+    fn needs_current_only_for_half_pairs() {
+        assert!(!needs_current(&FileAttributes::default()));
+        let both = FileAttributes {
+            uid: Some(1),
+            gid: Some(2),
+            accessed_at: Some(time(1)),
+            modified_at: Some(time(2)),
+            permissions: Some(0o644),
+            size: Some(0),
+        };
+        assert!(!needs_current(&both));
+        for attributes in [
+            FileAttributes { uid: None, ..both },
+            FileAttributes { gid: None, ..both },
+            FileAttributes {
+                accessed_at: None,
+                ..both
+            },
+            FileAttributes {
+                modified_at: None,
+                ..both
+            },
+        ] {
+            assert!(needs_current(&attributes), "{:?}", attributes);
+        }
+    }
+
+    #[test]
+    fn lone_uid_takes_gid_from_current() {
+        let attributes = FileAttributes {
+            uid: Some(0),
+            ..FileAttributes::default()
+        };
+        let metadata = to_metadata(&attributes, Some(&current())).unwrap();
+        assert_eq!((metadata.uid(), metadata.gid()), (Some(0), Some(100)));
+        assert!(metadata.accessed().is_none());
+
+        let attributes = FileAttributes {
+            gid: Some(0),
+            ..FileAttributes::default()
+        };
+        let metadata = to_metadata(&attributes, Some(&current())).unwrap();
+        assert_eq!((metadata.uid(), metadata.gid()), (Some(1000), Some(0)));
+    }
+
+    #[test]
+    fn lone_time_takes_partner_from_current() {
+        let attributes = FileAttributes {
+            accessed_at: Some(time(5_000)),
+            ..FileAttributes::default()
+        };
+        let metadata = to_metadata(&attributes, Some(&current())).unwrap();
+        assert_eq!(seconds(metadata.accessed()), Some(5_000));
+        assert_eq!(seconds(metadata.modified()), Some(2_000));
+        assert!(metadata.uid().is_none());
+
+        let attributes = FileAttributes {
+            modified_at: Some(time(6_000)),
+            ..FileAttributes::default()
+        };
+        let metadata = to_metadata(&attributes, Some(&current())).unwrap();
+        assert_eq!(seconds(metadata.accessed()), Some(1_000));
+        assert_eq!(seconds(metadata.modified()), Some(6_000));
+    }
+
+    #[test]
+    fn half_pair_without_current_is_an_error() {
+        let attributes = FileAttributes {
+            uid: Some(0),
+            ..FileAttributes::default()
+        };
+        assert!(to_metadata(&attributes, None).is_err());
+        let attributes = FileAttributes {
+            modified_at: Some(time(1)),
+            ..FileAttributes::default()
+        };
+        assert!(to_metadata(&attributes, None).is_err());
+        // Current attributes lacking the missing half are an error as well
+        let empty = MetaDataBuilder::new().create();
+        assert!(to_metadata(&attributes, Some(&empty)).is_err());
+    }
+
+    #[test]
+    fn full_pairs_do_not_need_current() {
+        let attributes = FileAttributes {
+            permissions: Some(0o4750),
+            uid: Some(1),
+            gid: Some(2),
+            size: Some(42),
+            accessed_at: Some(time(10)),
+            modified_at: Some(time(20)),
+        };
+        let metadata = to_metadata(&attributes, None).unwrap();
+        assert_eq!(metadata.permissions().map(mode), Some(0o4750));
+        assert_eq!(metadata.len(), Some(42));
+        assert_eq!((metadata.uid(), metadata.gid()), (Some(1), Some(2)));
+        assert_eq!(seconds(metadata.accessed()), Some(10));
+        assert_eq!(seconds(metadata.modified()), Some(20));
+    }
+}
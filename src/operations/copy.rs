@@ -0,0 +1,122 @@
+use anyhow::anyhow;
+use std::time::Instant;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::client::SftpClient;
+use crate::operations::download;
+use crate::remote_file::RemoteFile;
+use crate::types::{FileTransferOperationResult, FileTransferProgress};
+
+/// Copies a file to another path on the same remote server
+///
+/// When the server supports the `copy-data` extension (OpenSSH 9.0 and
+/// later), the copy is performed server-side and no file contents cross
+/// the network. Otherwise the file is streamed through the client using the
+/// concurrent read and ordered write logic of `get`.
+///
+/// # Arguments
+///
+/// * `client` - The SFTP client instance
+/// * `src_path` - Path of the remote file to copy
+/// * `dest_path` - Destination path on the same server, created or truncated
+/// * `cancel_token` - Token for cancelling the copy operation
+///
+/// # Returns
+///
+/// Returns a `FileTransferOperationResult`:
+/// - `Completed` with transfer progress if successful
+/// - `Cancelled` if the operation was cancelled. A server-side copy already
+///   sent to the server may still complete; a copy streamed through the
+///   client removes the partially written destination.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The source and destination paths resolve to the same file
+/// - The source file cannot be opened or read
+/// - The destination file cannot be opened, written to or closed
+/// - An SFTP error occurs during the copy
+pub async fn copy(
+    client: &SftpClient,
+    src_path: String,
+    dest_path: String,
+    cancel_token: CancellationToken,
+) -> Result<FileTransferOperationResult, anyhow::Error> {
+    let mut src_file = client.sftp.open(src_path.clone()).await?;
+    info!("Remote file opened: {:?}", src_path);
+    // The destination is only truncated once it is known not to be the
+    // source, so that an unusable destination fails before anything is lost
+    let mut dest_file = client
+        .sftp
+        .options()
+        .write(true)
+        .create(true)
+        .open(&dest_path)
+        .await?;
+    let mut fs = client.sftp.fs();
+    if fs.canonicalize(&src_path).await? == fs.canonicalize(&dest_path).await? {
+        return Err(anyhow!("{} and {} are the same file", src_path, dest_path));
+    }
+    dest_file.set_len(0).await?;
+    info!("Remote file created path: {:?}", dest_path);
+
+    if !client.sftp.support_copy() {
+        info!(
+            "copy-data extension not supported, streaming {:?} through the client",
+            src_path
+        );
+        let mut dest = RemoteFile::from_file(client, dest_path.clone(), dest_file);
+        let result = download::download(
+            client,
+            src_file,
+            src_path,
+            None,
+            &mut dest,
+            dest_path.clone(),
+            None,
+            cancel_token,
+        )
+        .await;
+        let result = match (result, dest.close().await) {
+            (Ok(result), Ok(())) => Ok(result),
+            (Err(e), _) | (Ok(_), Err(e)) => Err(e),
+        };
+        if !matches!(result, Ok(FileTransferOperationResult::Completed(_)))
+            && let Err(e) = fs.remove_file(&dest_path).await
+        {
+            warn!("Failed to remove partial copy {:?}: {}", dest_path, e);
+        }
+        return result;
+    }
+
+    let copy_time = Instant::now();
+    let file_size = src_file.metadata().await?.len();
+    tokio::select! {
+        _ = cancel_token.cancelled() => {
+            info!("Copy operation cancelled by user");
+            return Ok(FileTransferOperationResult::Cancelled {
+                src_file: src_path,
+                dest_file: dest_path,
+            });
+        }
+        copy_result = src_file.copy_all_to(&mut dest_file) => copy_result?,
+    }
+    dest_file.close().await?;
+    info!(
+        "File {:?} copied server-side to {:?}. Time taken {:?}",
+        src_path,
+        dest_path,
+        copy_time.elapsed()
+    );
+
+    Ok(FileTransferOperationResult::Completed(
+        FileTransferProgress {
+            src_file: src_path,
+            dest_file: dest_path,
+            file_size: file_size.unwrap_or(0),
+            bytes_transferred: file_size.unwrap_or(0),
+            percentage_progress: 100.0_f64,
+        },
+    ))
+}
@@ -1,18 +1,20 @@
 use anyhow::anyhow;
-use bytes::BytesMut;
-use futures::stream::{FuturesUnordered, StreamExt};
+use bytes::{Bytes, BytesMut};
+use futures::stream::{self, FuturesUnordered, Stream, StreamExt};
+use openssh_sftp_client::file::File;
 use std::collections::HashMap;
 use std::path::Path;
 use std::time::Instant;
 use tokio::fs;
-use tokio::io::{self, AsyncWriteExt};
+use tokio::io::{self, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
 use tokio::sync::mpsc;
-use tokio::task::JoinHandle;
 use tokio_util::sync::CancellationToken;
 use tracing::{error, info};
 
 use crate::client::SftpClient;
-use crate::types::{FileTransferOperationResult, FileTransferProgress};
+use crate::operations::stat;
+use crate::types::{FileTransferOperationResult, FileTransferProgress, FileType, SftpError};
+use crate::utils::ProgressReporter;
 
 /// Downloads a file from the remote server to local storage
 ///
@@ -39,6 +41,7 @@ use crate::types::{FileTransferOperationResult, FileTransferProgress};
 /// # Errors
 ///
 /// Returns an error if:
+/// - The remote file does not exist (`SftpError::NotFound`) or is a directory
 /// - The remote file cannot be opened or read
 /// - Parent directories for the local file cannot be created
 /// - The local file cannot be created or written to
@@ -50,89 +53,381 @@ pub async fn get(
     local_path: String,
     cancel_token: CancellationToken,
 ) -> Result<FileTransferOperationResult, anyhow::Error> {
-    let local_path_copy = local_path.clone();
+    // Fail before creating the local file if the source is missing or a directory
+    let remote = stat::stat(client, remote_path.clone()).await?;
+    if remote.file_type == FileType::Directory {
+        return Err(anyhow!("Remote path {:?} is a directory", remote_path));
+    }
+    let remote_file = client.sftp.open(remote_path.clone()).await?;
+    info!("Remote file opened: {:?}", remote_path);
+    let local_file = create_local_file(&local_path).await?;
+
+    download(
+        client,
+        remote_file,
+        remote_path,
+        None,
+        local_file,
+        local_path,
+        None,
+        cancel_token,
+    )
+    .await
+}
+
+/// Downloads a remote file into an `AsyncWrite` sink
+///
+/// Uses the same concurrent read and ordered write logic as [`get`], so a
+/// remote file can be piped directly into a parser, hasher or HTTP response.
+/// The writer is flushed once the download completes.
+///
+/// # Arguments
+///
+/// * `client` - The SFTP client instance
+/// * `remote_path` - Path to the remote file to download
+/// * `writer` - Destination of the file contents
+/// * `cancel_token` - Token for cancelling the download operation
+///
+/// # Returns
+///
+/// Returns a `FileTransferOperationResult`:
+/// - `Completed` with transfer progress if successful
+/// - `Cancelled` if the operation was cancelled
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The remote file cannot be opened or read
+/// - The writer returns an error
+/// - There's a channel communication error
+pub async fn get_to_writer(
+    client: &SftpClient,
+    remote_path: String,
+    writer: impl AsyncWrite,
+    cancel_token: CancellationToken,
+) -> Result<FileTransferOperationResult, anyhow::Error> {
+    let remote_file = client.sftp.open(remote_path.clone()).await?;
+    info!("Remote file opened: {:?}", remote_path);
+
+    download(
+        client,
+        remote_file,
+        remote_path,
+        None,
+        writer,
+        WRITER_DESTINATION.to_string(),
+        None,
+        cancel_token,
+    )
+    .await
+}
+
+/// Downloads a byte range of a remote file to local storage
+///
+/// Uses the same concurrent read and ordered write logic as [`get`], bounded
+/// to `len` bytes starting at `offset`. The range is truncated at the end of
+/// the file, so a range starting past the end produces an empty file.
+///
+/// # Arguments
+///
+/// * `client` - The SFTP client instance
+/// * `remote_path` - Path to the remote file to download
+/// * `local_path` - Local destination path
+/// * `offset` - Position of the first byte to download
+/// * `len` - Maximum number of bytes to download
+/// * `cancel_token` - Token for cancelling the download operation
+///
+/// # Returns
+///
+/// Returns a `FileTransferOperationResult`:
+/// - `Completed` with the number of bytes downloaded as `file_size`
+/// - `Cancelled` if the operation was cancelled
+///
+/// # Errors
+///
+/// Returns the same errors as [`get`]
+pub async fn get_range(
+    client: &SftpClient,
+    remote_path: String,
+    local_path: String,
+    offset: u64,
+    len: u64,
+    cancel_token: CancellationToken,
+) -> Result<FileTransferOperationResult, anyhow::Error> {
+    let remote_file = open_at(client, &remote_path, offset).await?;
+    let local_file = create_local_file(&local_path).await?;
+
+    download(
+        client,
+        remote_file,
+        remote_path,
+        Some(len),
+        local_file,
+        local_path,
+        None,
+        cancel_token,
+    )
+    .await
+}
+
+/// Downloads a byte range of a remote file into an `AsyncWrite` sink
+///
+/// See [`get_range`] for how the range is bounded and [`get_to_writer`] for
+/// how the writer is driven.
+///
+/// # Errors
+///
+/// Returns the same errors as [`get_to_writer`]
+pub async fn get_range_to_writer(
+    client: &SftpClient,
+    remote_path: String,
+    writer: impl AsyncWrite,
+    offset: u64,
+    len: u64,
+    cancel_token: CancellationToken,
+) -> Result<FileTransferOperationResult, anyhow::Error> {
+    let remote_file = open_at(client, &remote_path, offset).await?;
+
+    download(
+        client,
+        remote_file,
+        remote_path,
+        Some(len),
+        writer,
+        WRITER_DESTINATION.to_string(),
+        None,
+        cancel_token,
+    )
+    .await
+}
+
+/// Opens a remote file for reading, positioned at `offset`
+async fn open_at(
+    client: &SftpClient,
+    remote_path: &str,
+    offset: u64,
+) -> Result<File, anyhow::Error> {
+    let mut remote_file = client.sftp.open(remote_path).await?;
+    info!("Remote file opened: {:?}", remote_path);
+    remote_file.seek(io::SeekFrom::Start(offset)).await?;
+    Ok(remote_file)
+}
+
+/// Creates a local file, creating its parent directories first
+async fn create_local_file(local_path: &str) -> Result<fs::File, anyhow::Error> {
+    if let Some(parent) = Path::new(local_path).parent() {
+        fs::create_dir_all(parent)
+            .await
+            .map_err(|e| io::Error::other(format!("Failed to create parent directory: {:?}", e)))?;
+    }
+    let local_file = fs::File::create(local_path).await?;
+    info!("Local file created: {:?}", local_path);
+    Ok(local_file)
+}
+
+/// Destination name reported in the results of `get_to_writer`
+const WRITER_DESTINATION: &str = "<writer>";
+
+/// Downloads the contents of a remote file as a stream of byte chunks
+///
+/// Chunks of up to `io_size` bytes are yielded in file order as they are
+/// read, without buffering the whole file.
+///
+/// # Arguments
+///
+/// * `client` - The SFTP client instance
+/// * `remote_path` - Path to the remote file to download
+/// * `cancel_token` - Token for cancelling the download operation
+///
+/// # Returns
+///
+/// Returns a stream of `Bytes` chunks
+///
+/// # Errors
+///
+/// The stream yields an error and ends if:
+/// - The remote file cannot be opened or read
+/// - The operation is cancelled by the user (`SftpError::Cancelled`)
+pub fn get_stream(
+    client: &SftpClient,
+    remote_path: String,
+    cancel_token: CancellationToken,
+) -> impl Stream<Item = Result<Bytes, anyhow::Error>> + '_ {
+    let state = StreamState {
+        client,
+        remote_path,
+        cancel_token,
+        file: None,
+        done: false,
+    };
+
+    stream::unfold(state, |mut state| async move {
+        if state.done {
+            return None;
+        }
+        if state.file.is_none() {
+            match state.client.sftp.open(state.remote_path.clone()).await {
+                Ok(file) => {
+                    info!("Remote file opened: {:?}", state.remote_path);
+                    state.file = Some(file);
+                }
+                Err(e) => {
+                    state.done = true;
+                    return Some((Err(anyhow!("Failed to open remote file: {}", e)), state));
+                }
+            }
+        }
+
+        let io_size = state.client.config.io_size;
+        let file = state.file.as_mut()?;
+        tokio::select! {
+            _ = state.cancel_token.cancelled() => {
+                info!("Download stream cancelled by user");
+                state.done = true;
+                Some((Err(SftpError::Cancelled.into()), state))
+            }
+            read_result = file.read(io_size as u32, BytesMut::with_capacity(io_size)) => {
+                match read_result {
+                    Ok(Some(buf)) => Some((Ok(buf.freeze()), state)),
+                    Ok(None) => {
+                        info!("End of remote file reached");
+                        None
+                    }
+                    Err(e) => {
+                        error!("Error reading remote file: {:?}", e);
+                        state.done = true;
+                        Some((Err(anyhow!("Error reading from remote file: {e}")), state))
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// State of a download stream
+struct StreamState<'a> {
+    client: &'a SftpClient,
+    remote_path: String,
+    cancel_token: CancellationToken,
+    file: Option<File>,
+    done: bool,
+}
+
+/// Downloads an opened remote file into `writer`, from its current offset
+/// up to the end of the file or at most `len` bytes
+///
+/// `dest_file` is only used for logging and in the returned result. When a
+/// `progress` channel is given, the position in the remote file is reported
+/// after every chunk written.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn download(
+    client: &SftpClient,
+    mut remote_file: File,
+    remote_path: String,
+    len: Option<u64>,
+    writer: impl AsyncWrite,
+    dest_file: String,
+    progress: Option<mpsc::Sender<FileTransferProgress>>,
+    cancel_token: CancellationToken,
+) -> Result<FileTransferOperationResult, anyhow::Error> {
     let download_time = Instant::now();
     let mut is_cancelled: bool = false;
-    let mut remote_file = client.sftp.open(remote_path.clone()).await?;
-    info!("Remote file opened: {:?}", remote_path);
+    let start_offset = remote_file.offset();
     let remote_file_size = remote_file.metadata().await?.len();
+    let reporter = ProgressReporter {
+        sender: progress,
+        src_file: remote_path.clone(),
+        dest_file: dest_file.clone(),
+        file_size: remote_file_size.unwrap_or(0),
+    };
 
     let (tx, mut rx) = mpsc::channel::<(usize, Vec<u8>)>(client.config.concurrency);
 
     let mut buffer_idx = 0;
     let mut tasks = FuturesUnordered::new();
 
-    // Buffer map to ensure ordering in writing to the local file
+    // Buffer map to ensure ordering in writing to the destination
     let mut buffer_map = HashMap::new();
     let mut current_idx = 0;
 
-    // Writing to local file
-    let write_handle: JoinHandle<Result<(), io::Error>> = tokio::spawn(async move {
-        if let Some(parent) = Path::new(&local_path).parent() {
-            fs::create_dir_all(parent).await.map_err(|e| {
-                io::Error::new(
-                    io::ErrorKind::Other,
-                    format!("Failed to create parent directory: {:?}", e),
-                )
-            })?;
-        }
-        let mut local_file = fs::File::create(local_path.clone()).await?;
-        info!("Local file created: {:?}", local_path);
-
+    // Writing to the destination, driven concurrently with the reads below
+    let write_future = async move {
+        tokio::pin!(writer);
+        let mut position = start_offset;
         while let Some((idx, buffer)) = rx.recv().await {
             buffer_map.insert(idx, buffer);
 
             while let Some(buffer) = buffer_map.remove(&current_idx) {
-                local_file.write_all(&buffer).await?;
+                writer.write_all(&buffer).await?;
+                position += buffer.len() as u64;
+                reporter.report(position);
                 current_idx += 1;
             }
         }
-        Ok(())
-    });
+        writer.flush().await?;
+        Ok::<(), io::Error>(())
+    };
 
     // Reading from Remote file
     let mut download_error: Option<anyhow::Error> = None;
-    loop {
-        let buffer = BytesMut::with_capacity(client.config.io_size);
-        tokio::select! {
-            _ = cancel_token.cancelled() => {
-                info!("Operation cancelled by user");
-                is_cancelled = true;
+    let mut bytes_downloaded: u64 = 0;
+    let read_future = async {
+        loop {
+            let remaining = len.map(|len| len.saturating_sub(bytes_downloaded));
+            if remaining == Some(0) {
+                info!("End of requested range reached");
                 break;
             }
-            read_result = remote_file.read(client.config.io_size as u32, buffer) => {
-                let buf = match read_result {
-                    Ok(Some(buf)) => buf,
-                    Ok(None) => {
-                        info!("End of remote file reached");
-                        break;
-                    }
-                    Err(e) => {
-                        error!("Error reading remote file: {:?}", e);
-                        download_error = Some(anyhow!("Error reading from remote file: {e}"));
-                        break;
+            let read_size = remaining.map_or(client.config.io_size, |remaining| {
+                remaining.min(client.config.io_size as u64) as usize
+            });
+            let buffer = BytesMut::with_capacity(read_size);
+            tokio::select! {
+                _ = cancel_token.cancelled() => {
+                    info!("Operation cancelled by user");
+                    is_cancelled = true;
+                    break;
+                }
+                read_result = remote_file.read(read_size as u32, buffer) => {
+                    let buf = match read_result {
+                        Ok(Some(buf)) => buf,
+                        Ok(None) => {
+                            info!("End of remote file reached");
+                            break;
+                        }
+                        Err(e) => {
+                            error!("Error reading remote file: {:?}", e);
+                            download_error = Some(anyhow!("Error reading from remote file: {e}"));
+                            break;
+                        }
+                    };
+                    bytes_downloaded += buf.len() as u64;
+                    let tx = tx.clone();
+                    let data = buf[..buf.len()].to_vec();
+                    tasks.push(tokio::spawn(async move {
+                        tx.send((buffer_idx, data)).await.map_err(|_| {
+                            io::Error::new(io::ErrorKind::BrokenPipe, "Failed to send buffer")
+                        })
+                    }));
+                    buffer_idx += 1;
+                    if tasks.len() >= client.config.concurrency {
+                        tasks.select_next_some().await??;
                     }
-                };
-                let tx = tx.clone();
-                let data = buf[..buf.len()].to_vec();
-                tasks.push(tokio::spawn(async move {
-                    tx.send((buffer_idx, data)).await.map_err(|_| {
-                        io::Error::new(io::ErrorKind::BrokenPipe, "Failed to send buffer")
-                    })
-                }));
-                buffer_idx += 1;
-                if tasks.len() >= client.config.concurrency {
-                    tasks.select_next_some().await??;
                 }
             }
         }
-    }
 
-    drop(tx);
-    while let Some(task) = tasks.next().await {
-        task??;
-    }
-    write_handle.await??;
+        drop(tx);
+        while let Some(task) = tasks.next().await {
+            task??;
+        }
+        Ok::<(), anyhow::Error>(())
+    };
+
+    let (read_result, write_result) = tokio::join!(read_future, write_future);
+    // A write error closes the channel, so report it before the resulting send error
+    write_result?;
+    read_result?;
 
     // Intermediate remote file read error causing the read loop to terminate
     if let Some(err) = download_error {
@@ -147,15 +442,22 @@ pub async fn get(
 
     if is_cancelled {
         Ok(FileTransferOperationResult::Cancelled {
-            src_file: remote_path.clone(),
-            dest_file: local_path_copy.clone(),
+            src_file: remote_path,
+            dest_file,
         })
     } else {
         Ok(FileTransferOperationResult::Completed(
             FileTransferProgress {
-                src_file: remote_path.clone(),
-                dest_file: local_path_copy.clone(),
-                file_size: remote_file_size.unwrap(),
+                src_file: remote_path,
+                dest_file,
+                file_size: match len {
+                    Some(_) => bytes_downloaded,
+                    None => remote_file_size.unwrap_or(start_offset + bytes_downloaded),
+                },
+                bytes_transferred: match len {
+                    Some(_) => bytes_downloaded,
+                    None => start_offset + bytes_downloaded,
+                },
                 percentage_progress: 100.0_f64,
             },
         ))
@@ -0,0 +1,84 @@
+use futures::stream::TryStreamExt;
+use std::collections::BTreeMap;
+use std::path::{Component, PathBuf};
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+use crate::client::SftpClient;
+use crate::operations::walk;
+use crate::types::{DiskUsage, FileType, WalkOptions};
+
+/// Computes the disk usage of a remote directory tree
+///
+/// The tree is traversed with the concurrent walker, so up to
+/// `config.concurrency` directories are read at the same time. Symlinks are
+/// not followed and do not count towards the totals.
+///
+/// # Arguments
+///
+/// * `client` - The SFTP client instance
+/// * `remote_dir` - Path to the remote directory
+/// * `breakdown` - Whether to also compute the usage of each direct sub-directory
+/// * `cancel_token` - Token for cancelling the operation
+///
+/// # Returns
+///
+/// Returns the `DiskUsage` of everything below `remote_dir`
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - Any directory of the tree cannot be opened or read
+/// - The operation is cancelled by the user (`SftpError::Cancelled`)
+pub async fn du(
+    client: &SftpClient,
+    remote_dir: String,
+    breakdown: bool,
+    cancel_token: CancellationToken,
+) -> Result<DiskUsage, anyhow::Error> {
+    let root = PathBuf::from(&remote_dir);
+    let mut usage = DiskUsage::default();
+    let mut subdirectories = BTreeMap::new();
+
+    let entries = walk::walk(
+        client,
+        remote_dir.clone(),
+        WalkOptions::default(),
+        cancel_token,
+    );
+    futures::pin_mut!(entries);
+    while let Some(entry) = entries.try_next().await? {
+        let metadata = entry.metadata;
+        usage.add(metadata.file_type, metadata.size);
+
+        if !breakdown {
+            continue;
+        }
+        if entry.depth == 1 {
+            if metadata.file_type == FileType::Directory {
+                subdirectories
+                    .entry(metadata.path)
+                    .or_insert_with(DiskUsage::default);
+            }
+        } else if let Some(Component::Normal(top)) = metadata
+            .path
+            .strip_prefix(&root)
+            .ok()
+            .and_then(|relative| relative.components().next())
+        {
+            subdirectories
+                .entry(root.join(top))
+                .or_insert_with(DiskUsage::default)
+                .add(metadata.file_type, metadata.size);
+        }
+    }
+
+    info!(
+        "Disk usage of {:?}: {} bytes in {} files and {} directories",
+        remote_dir, usage.total_bytes, usage.file_count, usage.dir_count
+    );
+    if breakdown {
+        usage.subdirectories = Some(subdirectories);
+    }
+    Ok(usage)
+}
@@ -0,0 +1,462 @@
+use anyhow::anyhow;
+use futures::stream::{self, Stream, StreamExt};
+use globset::{GlobBuilder, GlobMatcher};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use tokio_util::sync::CancellationToken;
+
+use crate::client::SftpClient;
+use crate::operations::{list, stat};
+use crate::types::{FileMetadata, FileType};
+
+/// Single `/`-separated component of a glob pattern
+#[derive(Debug)]
+enum Component {
+    /// `**`: matches zero or more directories
+    Recursive,
+    /// Any other component, matched against a single file name
+    Name {
+        matcher: GlobMatcher,
+        /// Whether the component explicitly matches names starting with `.`
+        dot: bool,
+    },
+}
+
+impl Component {
+    fn matches(&self, name: &str) -> bool {
+        match self {
+            Component::Recursive => !name.starts_with('.'),
+            Component::Name { matcher, dot } => {
+                (*dot || !name.starts_with('.')) && matcher.is_match(name)
+            }
+        }
+    }
+}
+
+/// Directory still to be searched, with the indices of the components its
+/// entries are matched against
+#[derive(Debug)]
+struct Pending {
+    dir: PathBuf,
+    indices: Vec<usize>,
+}
+
+struct GlobState<'a> {
+    client: &'a SftpClient,
+    components: Vec<Component>,
+    pending: Vec<Pending>,
+    ready: VecDeque<FileMetadata>,
+    cancel_token: CancellationToken,
+}
+
+/// Expands a glob pattern on the remote server
+///
+/// The pattern is split on `/` and the literal leading directories are used
+/// as the starting point. Remaining components are matched by listing each
+/// candidate directory:
+/// - `*` matches any sequence of characters within a file name
+/// - `?` matches a single character
+/// - `[...]` matches a character class, e.g. `[0-9]` or `[!a-z]`
+/// - `**` matches zero or more directories
+/// - `{a,b}` matches either alternative within a single component; braces
+///   cannot contain `/`
+///
+/// As in the OpenSSH `sftp` client, wildcards do not match names starting
+/// with `.` unless the pattern component itself starts with `.`.
+///
+/// # Arguments
+///
+/// * `client` - The SFTP client instance
+/// * `pattern` - Glob pattern, e.g. `/data/2026-*/report_*.csv`
+/// * `cancel_token` - Token for cancelling the operation
+///
+/// # Returns
+///
+/// Returns a stream yielding the `FileMetadata` of every matching regular
+/// file or directory, sorted by name within each directory. A missing base
+/// directory yields no matches.
+///
+/// # Errors
+///
+/// The stream yields an error and ends if:
+/// - The pattern is invalid
+/// - A directory cannot be listed
+/// - The operation is cancelled by the user (`SftpError::Cancelled`)
+pub fn glob(
+    client: &SftpClient,
+    pattern: String,
+    cancel_token: CancellationToken,
+) -> impl Stream<Item = Result<FileMetadata, anyhow::Error>> + '_ {
+    let parsed = parse(&pattern);
+    let (base, components) = match parsed {
+        Ok(parsed) => parsed,
+        Err(e) => return stream::once(async { Err(e) }).left_stream(),
+    };
+
+    let state = GlobState {
+        client,
+        components,
+        pending: vec![Pending {
+            dir: base,
+            indices: vec![0],
+        }],
+        ready: VecDeque::new(),
+        cancel_token,
+    };
+
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(file) = state.ready.pop_front() {
+                return Some((Ok(file), state));
+            }
+            let pending = state.pending.pop()?;
+            if let Err(e) = expand(&mut state, pending).await {
+                state.pending.clear();
+                return Some((Err(e), state));
+            }
+        }
+    })
+    .right_stream()
+}
+
+/// Splits a pattern into its literal base directory and the components to match
+fn parse(pattern: &str) -> Result<(PathBuf, Vec<Component>), anyhow::Error> {
+    check_braces(pattern)?;
+    let mut base = if pattern.starts_with('/') {
+        PathBuf::from("/")
+    } else {
+        PathBuf::new()
+    };
+    let parts: Vec<&str> = pattern.split('/').filter(|p| !p.is_empty()).collect();
+    let Some((_, dirs)) = parts.split_last() else {
+        return Err(anyhow!("Empty glob pattern"));
+    };
+
+    // The last component is always matched against a listing of its parent
+    let literal = dirs.iter().take_while(|p| !has_wildcard(p)).count();
+    for part in &parts[..literal] {
+        base.push(part);
+    }
+
+    let components = parts[literal..]
+        .iter()
+        // Consecutive `**` match the same paths as a single one
+        .enumerate()
+        .filter(|(i, part)| !(**part == "**" && *i > 0 && parts[literal + i - 1] == "**"))
+        .map(|(_, part)| {
+            if *part == "**" {
+                return Ok(Component::Recursive);
+            }
+            let matcher = GlobBuilder::new(part)
+                .literal_separator(true)
+                .build()
+                .map_err(|e| anyhow!("Invalid glob pattern {:?}: {}", pattern, e))?
+                .compile_matcher();
+            Ok(Component::Name {
+                matcher,
+                dot: part.starts_with('.'),
+            })
+        })
+        .collect::<Result<Vec<_>, anyhow::Error>>()?;
+    Ok((base, components))
+}
+
+/// Rejects `/` inside `{...}`: components are matched one directory level at
+/// a time, so alternatives cannot span several levels
+fn check_braces(pattern: &str) -> Result<(), anyhow::Error> {
+    let (mut depth, mut in_class) = (0usize, false);
+    for c in pattern.chars() {
+        match c {
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '{' if !in_class => depth += 1,
+            '}' if !in_class => depth = depth.saturating_sub(1),
+            '/' if depth > 0 => {
+                return Err(anyhow!(
+                    "Invalid glob pattern {:?}: braces cannot contain '/'",
+                    pattern
+                ));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn has_wildcard(part: &str) -> bool {
+    part.contains(['*', '?', '[', '{'])
+}
+
+/// Lists one pending directory, queueing matches and sub-directories to search
+async fn expand(state: &mut GlobState<'_>, pending: Pending) -> Result<(), anyhow::Error> {
+    let listing_dir = if pending.dir.as_os_str().is_empty() {
+        ".".to_string()
+    } else {
+        pending.dir.to_string_lossy().into_owned()
+    };
+
+    let listing = list::read_dir(
+        state.client,
+        listing_dir.clone(),
+        state.cancel_token.clone(),
+    );
+    let entries = match listing.await {
+        Ok(entries) => entries,
+        // A missing directory has no matches
+        Err(e) => match stat::try_stat(state.client, listing_dir).await? {
+            None => return Ok(()),
+            Some(_) => return Err(e),
+        },
+    };
+
+    let (matches, subdirs) = match_entries(&state.components, pending, entries);
+    state.ready.extend(matches);
+    // Pending is a stack, push in reverse to keep name order
+    state.pending.extend(subdirs.into_iter().rev());
+    Ok(())
+}
+
+/// Matches the entries of a listed directory against the components of
+/// `pending`, returning the matches and the sub-directories to search
+///
+/// All the components a sub-directory has to be matched against are merged
+/// into a single `Pending`, so every directory is listed at most once.
+fn match_entries(
+    components: &[Component],
+    pending: Pending,
+    mut entries: Vec<FileMetadata>,
+) -> (Vec<FileMetadata>, Vec<Pending>) {
+    let Pending { dir, mut indices } = pending;
+    // `**` also matches zero directories, so the entries are matched against
+    // the following component as well
+    let mut i = 0;
+    while i < indices.len() {
+        let index = indices[i];
+        if matches!(components[index], Component::Recursive) && index + 1 < components.len() {
+            indices.push(index + 1);
+        }
+        i += 1;
+    }
+    indices.sort_unstable();
+    indices.dedup();
+
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    let mut matches = Vec::new();
+    let mut subdirs = Vec::new();
+    for mut entry in entries {
+        let Some(name) = entry.path.file_name() else {
+            continue;
+        };
+        let name = name.to_string_lossy().into_owned();
+        entry.path = dir.join(&name);
+        let is_dir = entry.file_type == FileType::Directory;
+
+        let mut matched = false;
+        let mut next = Vec::new();
+        for &index in &indices {
+            let component = &components[index];
+            if !component.matches(&name) {
+                continue;
+            }
+            let last = index + 1 == components.len();
+            // `**` keeps matching in sub-directories, other components move on to the next one
+            let recursive = matches!(component, Component::Recursive);
+            if is_dir && (recursive || !last) {
+                next.push(if recursive { index } else { index + 1 });
+            }
+            // A trailing `**` matches everything below `dir`
+            matched |= last;
+        }
+        if !next.is_empty() {
+            subdirs.push(Pending {
+                dir: entry.path.clone(),
+                indices: next,
+            });
+        }
+        if matched {
+            matches.push(entry);
+        }
+    }
+    (matches, subdirs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, file_type: FileType) -> FileMetadata {
+        FileMetadata {
+            path: PathBuf::from(name),
+            size: None,
+            file_type,
+            last_accessed_at: None,
+            last_modified_at: None,
+            permissions: None,
+            uid: None,
+            gid: None,
+            link_target: None,
+        }
+    }
+
+    fn pending(dir: &str, indices: &[usize]) -> Pending {
+        Pending {
+            dir: PathBuf::from(dir),
+            indices: indices.to_vec(),
+        }
+    }
+
+    fn paths(matches: &[FileMetadata]) -> Vec<&str> {
+        matches.iter().map(|m| m.path.to_str().unwrap()).collect()
+    }
+
+    fn subdirs(subdirs: &[Pending]) -> Vec<(&str, &[usize])> {
+        subdirs
+            .iter()
+            .map(|p| (p.dir.to_str().unwrap(), p.indices.as_slice()))
+            .collect()
+    }
+
+    #[test]
+    fn parse_splits_literal_base() {
+        let (base, components) = parse("/data/2026-*/report_*.csv").unwrap();
+        assert_eq!(base, PathBuf::from("/data"));
+        assert_eq!(components.len(), 2);
+
+        // The last component is matched even without wildcards
+        let (base, components) = parse("/data/report.csv").unwrap();
+        assert_eq!(base, PathBuf::from("/data"));
+        assert_eq!(components.len(), 1);
+        assert!(components[0].matches("report.csv"));
+
+        let (base, components) = parse("logs/*.gz").unwrap();
+        assert_eq!(base, PathBuf::from("logs"));
+        assert_eq!(components.len(), 1);
+
+        let (base, _) = parse("*.txt").unwrap();
+        assert_eq!(base, PathBuf::new());
+    }
+
+    #[test]
+    fn parse_collapses_consecutive_recursive() {
+        let (base, components) = parse("a/**/**/b").unwrap();
+        assert_eq!(base, PathBuf::from("a"));
+        assert_eq!(components.len(), 2);
+        assert!(matches!(components[0], Component::Recursive));
+    }
+
+    #[test]
+    fn parse_rejects_invalid_patterns() {
+        assert!(parse("").is_err());
+        assert!(parse("/").is_err());
+        assert!(parse("/data/[a-").is_err());
+    }
+
+    #[test]
+    fn parse_matches_braces_within_a_component() {
+        let (base, components) = parse("/var/log/{app,web}/*.{gz,log}").unwrap();
+        assert_eq!(base, PathBuf::from("/var/log"));
+        assert_eq!(components.len(), 2);
+        assert!(components[0].matches("app"));
+        assert!(components[0].matches("web"));
+        assert!(!components[0].matches("db"));
+        assert!(components[1].matches("a.log"));
+    }
+
+    #[test]
+    fn parse_rejects_slash_in_braces() {
+        let error = parse("{logs/a,b}/*.gz").unwrap_err();
+        assert!(error.to_string().contains("braces cannot contain '/'"));
+        assert!(parse("/data/{x,y/z}").is_err());
+        // Braces inside a character class are literal characters
+        assert!(parse("/data/[{]/x").is_ok());
+    }
+
+    #[test]
+    fn wildcards_skip_hidden_names() {
+        let (_, components) = parse("*").unwrap();
+        assert!(components[0].matches("file"));
+        assert!(!components[0].matches(".hidden"));
+
+        let (_, components) = parse(".*").unwrap();
+        assert!(components[0].matches(".hidden"));
+
+        let (_, components) = parse("**/x").unwrap();
+        assert!(components[0].matches("dir"));
+        assert!(!components[0].matches(".git"));
+    }
+
+    #[test]
+    fn name_component_descends_into_matching_dirs() {
+        let (_, components) = parse("/data/2026-*/*.csv").unwrap();
+        let entries = vec![
+            entry("2026-02", FileType::Directory),
+            entry("2026-01", FileType::Directory),
+            entry("2025-12", FileType::Directory),
+            entry("2026-03.csv", FileType::Regular),
+        ];
+        let (matches, next) = match_entries(&components, pending("/data", &[0]), entries);
+        assert!(matches.is_empty());
+        assert_eq!(
+            subdirs(&next),
+            [("/data/2026-01", &[1][..]), ("/data/2026-02", &[1][..])]
+        );
+
+        let entries = vec![
+            entry("b.csv", FileType::Regular),
+            entry("a.csv", FileType::Regular),
+            entry("c.txt", FileType::Regular),
+            entry("d.csv", FileType::Directory),
+        ];
+        let (matches, next) = match_entries(&components, pending("/data/2026-01", &[1]), entries);
+        assert_eq!(
+            paths(&matches),
+            [
+                "/data/2026-01/a.csv",
+                "/data/2026-01/b.csv",
+                "/data/2026-01/d.csv"
+            ]
+        );
+        assert!(next.is_empty());
+    }
+
+    #[test]
+    fn recursive_component_lists_each_directory_once() {
+        let (_, components) = parse("/logs/**/*.gz").unwrap();
+        let entries = vec![
+            entry("a.gz", FileType::Regular),
+            entry("app", FileType::Directory),
+            entry("old.gz", FileType::Directory),
+        ];
+        let (matches, next) = match_entries(&components, pending("/logs", &[0]), entries);
+        // `**` matching zero directories lets `*.gz` match in the same listing
+        assert_eq!(paths(&matches), ["/logs/a.gz", "/logs/old.gz"]);
+        // A single pending entry per directory, carrying only `**`, which
+        // brings `*.gz` back when the directory is listed
+        assert_eq!(
+            subdirs(&next),
+            [("/logs/app", &[0][..]), ("/logs/old.gz", &[0][..])]
+        );
+    }
+
+    #[test]
+    fn merges_indices_for_the_same_directory() {
+        let (_, components) = parse("**/a/*").unwrap();
+        let entries = vec![entry("a", FileType::Directory)];
+        let (matches, next) = match_entries(&components, pending("", &[0]), entries);
+        assert!(matches.is_empty());
+        assert_eq!(subdirs(&next), [("a", &[0, 2][..])]);
+    }
+
+    #[test]
+    fn trailing_recursive_matches_everything() {
+        let (_, components) = parse("/data/**").unwrap();
+        let entries = vec![
+            entry("file", FileType::Regular),
+            entry("dir", FileType::Directory),
+            entry(".hidden", FileType::Regular),
+        ];
+        let (matches, next) = match_entries(&components, pending("/data", &[0]), entries);
+        assert_eq!(paths(&matches), ["/data/dir", "/data/file"]);
+        assert_eq!(subdirs(&next), [("/data/dir", &[0][..])]);
+    }
+}
@@ -0,0 +1,125 @@
+use anyhow::anyhow;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+use crate::client::SftpClient;
+
+/// Creates a symbolic link at `link_path` pointing to `target`
+///
+/// `target` is stored as-is, so a relative target is resolved relative to the
+/// directory containing the link.
+///
+/// # Errors
+///
+/// Returns an error if the link cannot be created, e.g. because `link_path`
+/// already exists
+pub async fn symlink(
+    client: &SftpClient,
+    target: String,
+    link_path: String,
+) -> Result<(), anyhow::Error> {
+    client
+        .sftp
+        .fs()
+        .symlink(&target, &link_path)
+        .await
+        .map_err(|e| anyhow!("Failed to create symlink {:?}: {}", link_path, e))?;
+    info!("Symlink created: {:?} -> {:?}", link_path, target);
+    Ok(())
+}
+
+/// Points the symbolic link at `link_path` to `target`, replacing any
+/// existing file or link at that path
+///
+/// The new link is created under a temporary name next to `link_path` and
+/// renamed over it, so readers never observe a missing link. The switch is
+/// atomic when the server supports the `posix-rename@openssh.com`
+/// extension; other servers refuse to rename over an existing path.
+///
+/// # Errors
+///
+/// Returns an error if the temporary link cannot be created or renamed
+pub async fn replace_symlink(
+    client: &SftpClient,
+    target: String,
+    link_path: String,
+) -> Result<(), anyhow::Error> {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let file_name = Path::new(&link_path)
+        .file_name()
+        .ok_or_else(|| anyhow!("Invalid symlink path {:?}", link_path))?
+        .to_string_lossy();
+    let temp_path = Path::new(&link_path).with_file_name(format!(".{}.{}.tmp", file_name, nanos));
+
+    let mut fs = client.sftp.fs();
+    fs.symlink(&target, &temp_path)
+        .await
+        .map_err(|e| anyhow!("Failed to create symlink {:?}: {}", temp_path, e))?;
+    if let Err(e) = fs.rename(&temp_path, &link_path).await {
+        if let Err(e) = fs.remove_file(&temp_path).await {
+            warn!("Failed to remove temporary symlink {:?}: {}", temp_path, e);
+        }
+        return Err(anyhow!("Failed to replace symlink {:?}: {}", link_path, e));
+    }
+    info!("Symlink replaced: {:?} -> {:?}", link_path, target);
+    Ok(())
+}
+
+/// Reads the target of a symbolic link, without resolving it
+///
+/// # Errors
+///
+/// Returns an error if `link_path` does not exist or is not a symbolic link
+pub async fn read_link(client: &SftpClient, link_path: String) -> Result<PathBuf, anyhow::Error> {
+    client
+        .sftp
+        .fs()
+        .read_link(&link_path)
+        .await
+        .map_err(|e| anyhow!("Failed to read symlink {:?}: {}", link_path, e))
+}
+
+/// Creates a hard link at `link_path` to the existing file `src_path`
+///
+/// # Errors
+///
+/// Returns an error if the server does not support the
+/// `hardlink@openssh.com` extension or the link cannot be created
+pub async fn hard_link(
+    client: &SftpClient,
+    src_path: String,
+    link_path: String,
+) -> Result<(), anyhow::Error> {
+    if !client.sftp.support_hardlink() {
+        return Err(anyhow!(
+            "Server does not support the hardlink@openssh.com extension"
+        ));
+    }
+    client
+        .sftp
+        .fs()
+        .hard_link(&src_path, &link_path)
+        .await
+        .map_err(|e| anyhow!("Failed to create hard link {:?}: {}", link_path, e))?;
+    info!("Hard link created: {:?} -> {:?}", link_path, src_path);
+    Ok(())
+}
+
+/// Resolves a path to an absolute path with all symbolic links, `.` and
+/// `..` components resolved (realpath)
+///
+/// # Errors
+///
+/// Returns an error if the path cannot be resolved, e.g. because it does not exist
+pub async fn canonicalize(client: &SftpClient, path: String) -> Result<PathBuf, anyhow::Error> {
+    client
+        .sftp
+        .fs()
+        .canonicalize(&path)
+        .await
+        .map_err(|e| anyhow!("Failed to canonicalize {:?}: {}", path, e))
+}
@@ -1,11 +1,15 @@
 use anyhow::anyhow;
-use futures::stream::StreamExt;
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
+use openssh_sftp_client::fs::ReadDir;
 use std::path::PathBuf;
+use std::pin::Pin;
 use tokio_util::sync::CancellationToken;
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::client::SftpClient;
-use crate::types::{FileMetadata, FileType};
+use crate::filter::FileFilter;
+use crate::types::{FileMetadata, FileType, SftpError};
+use crate::utils::file_metadata;
 
 /// Lists the contents of a remote directory
 ///
@@ -17,76 +21,192 @@ use crate::types::{FileMetadata, FileType};
 ///
 /// # Returns
 ///
-/// Returns a vector of `FileMetadata` for all regular files in the directory.
-/// Note: This implementation currently only includes regular files, not directories.
+/// Returns a vector of `FileMetadata` for all regular files and symbolic links
+/// in the directory, with the target of each link in `link_target`.
+/// Note: This implementation does not include directories.
 ///
 /// # Errors
 ///
 /// Returns an error if:
 /// - The remote directory cannot be opened
 /// - There's an error reading directory entries
-/// - The operation is cancelled by the user
+/// - The operation is cancelled by the user (`SftpError::Cancelled`)
 pub async fn ls(
     client: &SftpClient,
     remote_dir: String,
     cancel_token: CancellationToken,
 ) -> Result<Vec<FileMetadata>, anyhow::Error> {
-    let mut file_list = Vec::new();
-    let dir = client
-        .sftp
-        .fs()
-        .open_dir(remote_dir.clone())
+    ls_stream(client, remote_dir, cancel_token)
+        .try_collect()
         .await
-        .map_err(|e| anyhow!("Failed to open remote dir: {}", e))?;
+}
 
-    let dir_stream = dir.read_dir();
-    futures::pin_mut!(dir_stream);
-    let mut error: Option<anyhow::Error> = None;
+/// Lists the contents of a remote directory as a stream
+///
+/// Entries are yielded as soon as the server sends them instead of being
+/// collected first, which keeps memory usage flat for very large directories.
+///
+/// # Arguments
+///
+/// * `client` - The SFTP client instance
+/// * `remote_dir` - Path to the remote directory
+/// * `cancel_token` - Token for cancelling the operation
+///
+/// # Returns
+///
+/// Returns a stream of `FileMetadata` for all regular files and symbolic links
+/// in the directory
+///
+/// # Errors
+///
+/// The stream yields an error and ends if:
+/// - The remote directory cannot be opened
+/// - There's an error reading directory entries
+/// - The operation is cancelled by the user (`SftpError::Cancelled`)
+pub fn ls_stream(
+    client: &SftpClient,
+    remote_dir: String,
+    cancel_token: CancellationToken,
+) -> impl Stream<Item = Result<FileMetadata, anyhow::Error>> + '_ {
+    entries_stream(client, remote_dir, cancel_token, false)
+}
+
+/// Lists the regular files and symbolic links of a remote directory accepted by a filter
+///
+/// # Arguments
+///
+/// * `client` - The SFTP client instance
+/// * `remote_dir` - Path to the remote directory
+/// * `filter` - Filter evaluated against each file, with patterns matched on the file name
+/// * `cancel_token` - Token for cancelling the operation
+///
+/// # Errors
+///
+/// Returns the same errors as [`ls`]
+pub async fn ls_filtered(
+    client: &SftpClient,
+    remote_dir: String,
+    filter: &FileFilter,
+    cancel_token: CancellationToken,
+) -> Result<Vec<FileMetadata>, anyhow::Error> {
+    let mut files = ls(client, remote_dir, cancel_token).await?;
+    files.retain(|file| filter.matches(file));
+    Ok(files)
+}
 
-    loop {
-        tokio::select! {
-            _ = cancel_token.cancelled() => {
-                info!("ls operation cancelled by user");
-                break;
+/// Lists the regular files, sub-directories and symlinks of a remote directory
+///
+/// Used internally by the recursive operations which need to descend into
+/// sub-directories. Special files (sockets, devices, fifos) are skipped.
+///
+/// # Arguments
+///
+/// * `client` - The SFTP client instance
+/// * `remote_dir` - Path to the remote directory
+/// * `cancel_token` - Token for cancelling the operation
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The remote directory cannot be opened
+/// - There's an error reading directory entries
+pub(crate) async fn read_dir(
+    client: &SftpClient,
+    remote_dir: String,
+    cancel_token: CancellationToken,
+) -> Result<Vec<FileMetadata>, anyhow::Error> {
+    entries_stream(client, remote_dir, cancel_token, true)
+        .try_collect()
+        .await
+}
+
+/// State of a directory listing stream
+struct ListState<'a> {
+    client: &'a SftpClient,
+    remote_dir: String,
+    cancel_token: CancellationToken,
+    include_dirs: bool,
+    entries: Option<Pin<Box<ReadDir>>>,
+    done: bool,
+}
+
+fn entries_stream(
+    client: &SftpClient,
+    remote_dir: String,
+    cancel_token: CancellationToken,
+    include_dirs: bool,
+) -> impl Stream<Item = Result<FileMetadata, anyhow::Error>> + '_ {
+    let state = ListState {
+        client,
+        remote_dir,
+        cancel_token,
+        include_dirs,
+        entries: None,
+        done: false,
+    };
+
+    stream::unfold(state, |mut state| async move {
+        if state.done {
+            return None;
+        }
+        if state.entries.is_none() {
+            match state
+                .client
+                .sftp
+                .fs()
+                .open_dir(state.remote_dir.clone())
+                .await
+            {
+                Ok(dir) => state.entries = Some(Box::pin(dir.read_dir())),
+                Err(e) => {
+                    state.done = true;
+                    return Some((Err(anyhow!("Failed to open remote dir: {}", e)), state));
+                }
             }
-            entry_opt = dir_stream.next() => {
-                match entry_opt {
-                    Some(Ok(entry)) => {
-                        let file_type = entry.file_type();
-                        let file_name = entry.filename().file_name();
-                        if let (Some(file_type), Some(file_name)) = (file_type, file_name) {
-                            // Currently only listing regular files
-                            if file_type.is_file() {
-                                let metadata = entry.metadata();
-                                file_list.push(
-                                    FileMetadata {
-                                        path: PathBuf::from(remote_dir.clone()).join(file_name.to_string_lossy().as_ref()),
-                                        file_type: if file_type.is_dir() {
-                                            FileType::Directory
-                                        } else {
-                                            FileType::Regular
-                                        },
-                                        size: metadata.len(),
-                                        last_accessed_at: metadata.accessed().map(|t| t.as_system_time()),
-                                        last_modified_at: metadata.modified().map(|t| t.as_system_time())
-                                    }
-                                );
+        }
+
+        loop {
+            let entries = state.entries.as_mut()?;
+            let mut file = tokio::select! {
+                _ = state.cancel_token.cancelled() => {
+                    info!("ls operation cancelled by user");
+                    state.done = true;
+                    return Some((Err(SftpError::Cancelled.into()), state));
+                }
+                entry_opt = entries.next() => {
+                    match entry_opt {
+                        Some(Ok(entry)) => {
+                            let file_type = entry.file_type();
+                            let file_name = entry.filename().file_name();
+                            match (file_type, file_name) {
+                                (Some(file_type), Some(file_name))
+                                    if file_type.is_file()
+                                        || file_type.is_symlink()
+                                        || (state.include_dirs && file_type.is_dir()) =>
+                                {
+                                    let name = file_name.to_string_lossy();
+                                    let path = PathBuf::from(&state.remote_dir).join(name.as_ref());
+                                    file_metadata(path, &entry.metadata())
+                                }
+                                _ => continue,
                             }
                         }
+                        Some(Err(e)) => {
+                            state.done = true;
+                            return Some((Err(anyhow!("Failed to ls to remote directory: {e}")), state));
+                        }
+                        None => return None,
                     }
-                    Some(Err(e)) => {
-                        error = Some(anyhow!("Failed to ls to remote directory: {e}"));
-                        break;
-                    }
-                    None => break,
+                }
+            };
+
+            if file.file_type == FileType::Symlink {
+                match state.client.sftp.fs().read_link(&file.path).await {
+                    Ok(target) => file.link_target = Some(target),
+                    Err(e) => warn!("Failed to read symlink {:?}: {}", file.path, e),
                 }
             }
+            return Some((Ok(file), state));
         }
-    }
-
-    if let Some(e) = error {
-        Err(e)
-    } else {
-        Ok(file_list)
-    }
+    })
 }
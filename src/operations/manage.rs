@@ -0,0 +1,158 @@
+use anyhow::anyhow;
+use futures::stream::TryStreamExt;
+use std::path::{Path, PathBuf};
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+use crate::client::SftpClient;
+use crate::operations::{stat, walk};
+use crate::types::{FileType, SftpError, WalkOptions};
+
+/// Creates a remote directory
+///
+/// # Errors
+///
+/// Returns an error if the directory already exists or its parent does not exist
+pub async fn create_dir(client: &SftpClient, remote_dir: String) -> Result<(), anyhow::Error> {
+    client
+        .sftp
+        .fs()
+        .create_dir(&remote_dir)
+        .await
+        .map_err(|e| anyhow!("Failed to create remote dir {:?}: {}", remote_dir, e))?;
+    info!("Remote dir created: {:?}", remote_dir);
+    Ok(())
+}
+
+/// Creates a remote directory and all of its missing parents, like `mkdir -p`
+///
+/// # Errors
+///
+/// Returns an error if a directory cannot be created, or if the path or one
+/// of its parents exists but is not a directory
+pub async fn create_dir_all(client: &SftpClient, remote_dir: String) -> Result<(), anyhow::Error> {
+    // Find the deepest existing ancestor, then create the missing directories top-down
+    let mut missing = Vec::new();
+    let mut current = Some(PathBuf::from(&remote_dir));
+    while let Some(dir) = current {
+        if dir.as_os_str().is_empty() {
+            break;
+        }
+        match stat::try_stat(client, dir.to_string_lossy().into_owned()).await? {
+            Some(file) if file.file_type == FileType::Directory => break,
+            Some(_) => return Err(anyhow!("Remote path {:?} is not a directory", dir)),
+            None => {
+                current = dir.parent().map(Path::to_path_buf);
+                missing.push(dir);
+            }
+        }
+    }
+    for dir in missing.into_iter().rev() {
+        create_dir(client, dir.to_string_lossy().into_owned()).await?;
+    }
+    Ok(())
+}
+
+/// Removes a remote file or symbolic link
+///
+/// # Errors
+///
+/// Returns an error if the file does not exist or cannot be removed
+pub async fn remove_file(client: &SftpClient, remote_path: String) -> Result<(), anyhow::Error> {
+    client
+        .sftp
+        .fs()
+        .remove_file(&remote_path)
+        .await
+        .map_err(|e| anyhow!("Failed to remove remote file {:?}: {}", remote_path, e))?;
+    info!("Remote file removed: {:?}", remote_path);
+    Ok(())
+}
+
+/// Removes an empty remote directory
+///
+/// # Errors
+///
+/// Returns an error if the directory does not exist, is not empty or cannot be removed
+pub async fn remove_dir(client: &SftpClient, remote_dir: String) -> Result<(), anyhow::Error> {
+    client
+        .sftp
+        .fs()
+        .remove_dir(&remote_dir)
+        .await
+        .map_err(|e| anyhow!("Failed to remove remote dir {:?}: {}", remote_dir, e))?;
+    info!("Remote dir removed: {:?}", remote_dir);
+    Ok(())
+}
+
+/// Removes a remote directory and all of its contents, like `rm -r`
+///
+/// Symbolic links are removed, never followed. Directories are removed
+/// deepest first once their contents are gone.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The directory cannot be listed
+/// - An entry cannot be removed
+/// - The operation is cancelled by the user (`SftpError::Cancelled`); entries
+///   removed before the cancellation stay removed
+pub async fn remove_dir_all(
+    client: &SftpClient,
+    remote_dir: String,
+    cancel_token: CancellationToken,
+) -> Result<(), anyhow::Error> {
+    let entries: Vec<_> = walk::walk(
+        client,
+        remote_dir.clone(),
+        WalkOptions::default(),
+        cancel_token.clone(),
+    )
+    .try_collect()
+    .await?;
+
+    let mut dirs = Vec::new();
+    for entry in entries {
+        if cancel_token.is_cancelled() {
+            return Err(SftpError::Cancelled.into());
+        }
+        let path = entry.metadata.path.to_string_lossy().into_owned();
+        match entry.metadata.file_type {
+            FileType::Directory => dirs.push((entry.depth, path)),
+            FileType::Regular | FileType::Symlink => remove_file(client, path).await?,
+        }
+    }
+    // Deepest first, so that every directory is empty when removed
+    dirs.sort_by_key(|(depth, _)| std::cmp::Reverse(*depth));
+    for (_, dir) in dirs {
+        if cancel_token.is_cancelled() {
+            return Err(SftpError::Cancelled.into());
+        }
+        remove_dir(client, dir).await?;
+    }
+    remove_dir(client, remote_dir).await
+}
+
+/// Renames or moves a remote file or directory
+///
+/// Uses the `posix-rename@openssh.com` extension when available, which
+/// atomically replaces an existing destination; otherwise the rename fails if
+/// the destination exists.
+///
+/// # Errors
+///
+/// Returns an error if the source does not exist or cannot be renamed
+pub async fn rename(
+    client: &SftpClient,
+    src_path: String,
+    dest_path: String,
+) -> Result<(), anyhow::Error> {
+    client
+        .sftp
+        .fs()
+        .rename(&src_path, &dest_path)
+        .await
+        .map_err(|e| anyhow!("Failed to rename {:?} to {:?}: {}", src_path, dest_path, e))?;
+    info!("Renamed {:?} to {:?}", src_path, dest_path);
+    Ok(())
+}
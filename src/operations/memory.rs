@@ -0,0 +1,104 @@
+use anyhow::anyhow;
+use openssh_sftp_client::file::TokioCompatFile;
+use tokio::io::AsyncReadExt;
+use tracing::{error, info};
+
+use crate::client::SftpClient;
+use crate::types::SftpError;
+
+/// Reads a whole remote file into memory
+///
+/// Intended for small files such as configuration files and markers. The
+/// file is rejected if it is larger than `max_in_memory_size`, both when the
+/// server reports its size up front and when more data than announced is read.
+///
+/// # Arguments
+///
+/// * `client` - The SFTP client instance
+/// * `remote_path` - Path to the remote file
+///
+/// # Returns
+///
+/// Returns the contents of the file
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The remote file cannot be opened or read
+/// - The file exceeds `max_in_memory_size` (`SftpError::FileTooLarge`)
+pub async fn read_to_bytes(
+    client: &SftpClient,
+    remote_path: String,
+) -> Result<Vec<u8>, anyhow::Error> {
+    let limit = client.config.max_in_memory_size;
+    let mut remote_file = client.sftp.open(remote_path.clone()).await?;
+    info!("Remote file opened: {:?}", remote_path);
+
+    let size = remote_file.metadata().await?.len();
+    if let Some(size) = size
+        && size > limit
+    {
+        error!(
+            "Remote file {:?} is too large to read into memory: {} bytes",
+            remote_path, size
+        );
+        return Err(SftpError::FileTooLarge { size, limit }.into());
+    }
+
+    let mut contents = Vec::with_capacity(size.unwrap_or(0) as usize);
+    // Read one byte past the limit to detect files which grew after the size check
+    let reader = TokioCompatFile::new(remote_file).take(limit.saturating_add(1));
+    tokio::pin!(reader);
+    reader.read_to_end(&mut contents).await?;
+    if contents.len() as u64 > limit {
+        error!(
+            "Remote file {:?} is too large to read into memory",
+            remote_path
+        );
+        return Err(SftpError::FileTooLarge {
+            size: contents.len() as u64,
+            limit,
+        }
+        .into());
+    }
+    Ok(contents)
+}
+
+/// Reads a whole remote UTF-8 text file into memory
+///
+/// # Errors
+///
+/// Returns the same errors as [`read_to_bytes`], or an error if the file is
+/// not valid UTF-8
+pub async fn read_to_string(
+    client: &SftpClient,
+    remote_path: String,
+) -> Result<String, anyhow::Error> {
+    let contents = read_to_bytes(client, remote_path.clone()).await?;
+    String::from_utf8(contents)
+        .map_err(|e| anyhow!("Remote file {:?} is not valid UTF-8: {}", remote_path, e))
+}
+
+/// Writes a buffer to a remote file, creating or truncating it
+///
+/// # Arguments
+///
+/// * `client` - The SFTP client instance
+/// * `remote_path` - Destination path on the remote server
+/// * `data` - Contents of the file
+///
+/// # Errors
+///
+/// Returns an error if the remote file cannot be created or written to
+pub async fn write_bytes(
+    client: &SftpClient,
+    remote_path: String,
+    data: &[u8],
+) -> Result<(), anyhow::Error> {
+    let mut remote_file = client.sftp.create(remote_path.clone()).await?;
+    info!("Remote file created path: {:?}", remote_path);
+    remote_file.write_all(data).await?;
+    remote_file.close().await?;
+    info!("Wrote {} bytes to {:?}", data.len(), remote_path);
+    Ok(())
+}
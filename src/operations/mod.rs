@@ -1,8 +1,47 @@
+/// Module for changing the attributes of remote files
+pub(crate) mod attributes;
+
+/// Module for copying files on the remote server
+pub(crate) mod copy;
+
 /// Module for downloading files from remote server
 pub(crate) mod download;
 
+/// Module for computing the disk usage of remote directory trees
+pub(crate) mod du;
+
+/// Module for expanding glob patterns on the remote server
+pub(crate) mod glob;
+
+/// Module for creating and resolving symbolic and hard links
+pub(crate) mod link;
+
 /// Module for listing remote directory contents
 pub(crate) mod list;
 
+/// Module for creating, renaming and removing remote files and directories
+pub(crate) mod manage;
+
+/// Module for reading and writing small remote files in memory
+pub(crate) mod memory;
+
+/// Module for following growing remote files
+pub(crate) mod tail;
+
+/// Module for transferring files between two remote servers
+pub(crate) mod transfer;
+
 /// Module for uploading files to remote server
 pub(crate) mod upload;
+
+/// Module for querying the metadata of single remote paths
+pub(crate) mod stat;
+
+/// Module for querying remote filesystem capacity
+pub(crate) mod statvfs;
+
+/// Module for mirroring directory trees between local and remote
+pub(crate) mod sync;
+
+/// Module for recursively walking remote directory trees
+pub(crate) mod walk;
@@ -0,0 +1,81 @@
+use openssh_sftp_client::error::SftpErrorKind;
+use std::path::PathBuf;
+use tracing::warn;
+
+use crate::client::SftpClient;
+use crate::types::{FileMetadata, FileType, SftpError};
+use crate::utils::file_metadata;
+
+/// Queries the metadata of a single remote path, following symbolic links
+///
+/// # Arguments
+///
+/// * `client` - The SFTP client instance
+/// * `remote_path` - Path to the remote file or directory
+///
+/// # Returns
+///
+/// Returns the `FileMetadata` of the path, or of the target of a symbolic link
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The path (or the target of a symbolic link) does not exist (`SftpError::NotFound`)
+/// - The metadata cannot be queried
+pub async fn stat(client: &SftpClient, remote_path: String) -> Result<FileMetadata, anyhow::Error> {
+    match client.sftp.fs().metadata(&remote_path).await {
+        Ok(metadata) => Ok(file_metadata(PathBuf::from(remote_path), &metadata)),
+        Err(e) => Err(map_error(e, remote_path)),
+    }
+}
+
+/// Queries the metadata of a single remote path without following symbolic links
+///
+/// For a symbolic link, the metadata of the link itself is returned, with its
+/// target in `link_target`.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The path does not exist (`SftpError::NotFound`)
+/// - The metadata cannot be queried
+pub async fn lstat(
+    client: &SftpClient,
+    remote_path: String,
+) -> Result<FileMetadata, anyhow::Error> {
+    let mut fs = client.sftp.fs();
+    let metadata = match fs.symlink_metadata(&remote_path).await {
+        Ok(metadata) => metadata,
+        Err(e) => return Err(map_error(e, remote_path)),
+    };
+    let mut file = file_metadata(PathBuf::from(&remote_path), &metadata);
+    if file.file_type == FileType::Symlink {
+        match fs.read_link(&remote_path).await {
+            Ok(target) => file.link_target = Some(target),
+            Err(e) => warn!("Failed to read symlink {:?}: {}", remote_path, e),
+        }
+    }
+    Ok(file)
+}
+
+/// Queries the metadata of a remote path, returning `None` if it does not exist
+pub(crate) async fn try_stat(
+    client: &SftpClient,
+    remote_path: String,
+) -> Result<Option<FileMetadata>, anyhow::Error> {
+    match stat(client, remote_path).await {
+        Ok(file) => Ok(Some(file)),
+        Err(e) if matches!(e.downcast_ref(), Some(SftpError::NotFound { .. })) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Converts a missing path error into `SftpError::NotFound`
+fn map_error(error: openssh_sftp_client::Error, remote_path: String) -> anyhow::Error {
+    match error {
+        openssh_sftp_client::Error::SftpError(SftpErrorKind::NoSuchFile, _) => {
+            SftpError::NotFound { path: remote_path }.into()
+        }
+        e => e.into(),
+    }
+}
@@ -0,0 +1,273 @@
+use anyhow::anyhow;
+use openssh::{Child, ChildStdin, ChildStdout, Session, Stdio};
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tracing::debug;
+
+use crate::client::SftpClient;
+use crate::types::DiskSpace;
+
+const SSH_FXP_INIT: u8 = 1;
+const SSH_FXP_VERSION: u8 = 2;
+const SSH_FXP_STATUS: u8 = 101;
+const SSH_FXP_EXTENDED: u8 = 200;
+const SSH_FXP_EXTENDED_REPLY: u8 = 201;
+const SFTP_VERSION: u32 = 3;
+const STATVFS_EXTENSION: &str = "statvfs@openssh.com";
+
+/// Queries the capacity of the remote filesystem containing a path
+///
+/// The `statvfs@openssh.com` extension is not exposed by `openssh-sftp-client`,
+/// so this function uses a separate `sftp` subsystem channel on the client's
+/// SSH session and performs the exchange directly:
+/// 1. On first use, opens the channel, sends `SSH_FXP_INIT` and checks the
+///    server advertises the extension
+/// 2. Sends `SSH_FXP_EXTENDED` with the extension name and the path
+/// 3. Decodes the `SSH_FXP_EXTENDED_REPLY` into a `DiskSpace`
+///
+/// The channel is kept open on the client for later calls, and reopened if
+/// an exchange fails.
+///
+/// # Arguments
+///
+/// * `client` - The SFTP client instance
+/// * `remote_path` - Any path on the filesystem to query
+///
+/// # Returns
+///
+/// Returns the `DiskSpace` of the filesystem containing `remote_path`
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The sftp subsystem channel cannot be opened
+/// - The server does not support the `statvfs@openssh.com` extension
+/// - The server reports an error for the path (e.g. it does not exist)
+/// - The server response is malformed, or reports sizes overflowing 64 bits
+pub async fn disk_space(
+    client: &SftpClient,
+    remote_path: String,
+) -> Result<DiskSpace, anyhow::Error> {
+    let mut channel = client.statvfs.lock().await;
+    let mut open = match channel.take() {
+        Some(open) => open,
+        None => StatvfsChannel::open(client).await?,
+    };
+    if !open.supported {
+        *channel = Some(open);
+        return Err(anyhow!(
+            "Server does not support the {} extension",
+            STATVFS_EXTENSION
+        ));
+    }
+    // A channel which failed an exchange is dropped, the next call reopens it
+    let reply = open.request(&remote_path).await?;
+    *channel = Some(open);
+    drop(channel);
+
+    let mut reader = PacketReader::new(&reply);
+    match reader.u8()? {
+        SSH_FXP_EXTENDED_REPLY => {
+            let mut fields = [0u64; 11];
+            for field in fields.iter_mut() {
+                *field = reader.u64()?;
+            }
+            let [
+                _bsize,
+                frsize,
+                blocks,
+                bfree,
+                bavail,
+                files,
+                ffree,
+                favail,
+                _fsid,
+                _flag,
+                namemax,
+            ] = fields;
+            // The values come from the server, bogus ones must not overflow
+            let bytes = |count: u64| {
+                count.checked_mul(frsize).ok_or_else(|| {
+                    anyhow!(
+                        "statvfs reported {} blocks of {} bytes, which overflows 64 bits",
+                        count,
+                        frsize
+                    )
+                })
+            };
+            let space = DiskSpace {
+                block_size: frsize,
+                total_bytes: bytes(blocks)?,
+                free_bytes: bytes(bfree)?,
+                available_bytes: bytes(bavail)?,
+                total_inodes: files,
+                free_inodes: ffree,
+                available_inodes: favail,
+                max_name_length: namemax,
+            };
+            debug!("statvfs {:?}: {:?}", remote_path, space);
+            Ok(space)
+        }
+        SSH_FXP_STATUS => {
+            let code = reader.u32()?;
+            let message = String::from_utf8_lossy(reader.string()?).into_owned();
+            Err(anyhow!(
+                "statvfs failed for {:?}: {} (status {})",
+                remote_path,
+                message,
+                code
+            ))
+        }
+        other => Err(anyhow!("Unexpected sftp packet type {} for statvfs", other)),
+    }
+}
+
+/// `sftp` subsystem channel used for `statvfs@openssh.com` requests
+#[derive(Debug)]
+pub(crate) struct StatvfsChannel {
+    child: Child<Arc<Session>>,
+    stdin: ChildStdin,
+    stdout: ChildStdout,
+    /// Whether the server advertised the extension in its handshake
+    supported: bool,
+    next_id: u32,
+}
+
+impl StatvfsChannel {
+    /// Opens the channel and performs the sftp handshake
+    async fn open(client: &SftpClient) -> Result<Self, anyhow::Error> {
+        let mut child = Session::to_subsystem(client.session.clone(), "sftp")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .await?;
+        let mut stdin = child
+            .stdin()
+            .take()
+            .ok_or(anyhow!("Failed to open sftp subsystem stdin"))?;
+        let mut stdout = child
+            .stdout()
+            .take()
+            .ok_or(anyhow!("Failed to open sftp subsystem stdout"))?;
+
+        let mut init = Vec::new();
+        init.push(SSH_FXP_INIT);
+        init.extend_from_slice(&SFTP_VERSION.to_be_bytes());
+        stdin.write_all(&frame(&init)).await?;
+        stdin.flush().await?;
+
+        let version = read_packet(&mut stdout).await?;
+        let mut reader = PacketReader::new(&version);
+        if reader.u8()? != SSH_FXP_VERSION {
+            return Err(anyhow!("Unexpected sftp handshake response"));
+        }
+        reader.u32()?;
+        let mut supported = false;
+        while !reader.is_empty() {
+            let name = reader.string()?;
+            reader.string()?;
+            supported |= name == STATVFS_EXTENSION.as_bytes();
+        }
+        debug!("Opened statvfs channel, extension supported: {}", supported);
+
+        Ok(Self {
+            child,
+            stdin,
+            stdout,
+            supported,
+            next_id: 0,
+        })
+    }
+
+    /// Sends a statvfs request and returns the reply without its request id
+    async fn request(&mut self, remote_path: &str) -> Result<Vec<u8>, anyhow::Error> {
+        self.next_id = self.next_id.wrapping_add(1);
+        let mut request = Vec::new();
+        request.push(SSH_FXP_EXTENDED);
+        request.extend_from_slice(&self.next_id.to_be_bytes());
+        put_string(&mut request, STATVFS_EXTENSION.as_bytes());
+        put_string(&mut request, remote_path.as_bytes());
+        self.stdin.write_all(&frame(&request)).await?;
+        self.stdin.flush().await?;
+
+        let mut reply = read_packet(&mut self.stdout).await?;
+        if reply.len() < 5 || reply[1..5] != self.next_id.to_be_bytes() {
+            return Err(anyhow!("Unexpected response id for statvfs request"));
+        }
+        reply.drain(1..5);
+        Ok(reply)
+    }
+
+    /// Ends the sftp subsystem and waits for it to exit
+    pub(crate) async fn close(self) -> Result<(), anyhow::Error> {
+        drop(self.stdin);
+        self.child.wait().await?;
+        Ok(())
+    }
+}
+
+/// Prefixes a packet payload with its length
+fn frame(payload: &[u8]) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(payload.len() + 4);
+    packet.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    packet.extend_from_slice(payload);
+    packet
+}
+
+fn put_string(buffer: &mut Vec<u8>, value: &[u8]) {
+    buffer.extend_from_slice(&(value.len() as u32).to_be_bytes());
+    buffer.extend_from_slice(value);
+}
+
+async fn read_packet<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Vec<u8>, anyhow::Error> {
+    let len = reader.read_u32().await? as usize;
+    // Same limit as openssh's sftp-server
+    if len > 256 * 1024 {
+        return Err(anyhow!("sftp packet of {} bytes is too long", len));
+    }
+    let mut packet = vec![0; len];
+    reader.read_exact(&mut packet).await?;
+    Ok(packet)
+}
+
+/// Cursor over the payload of a received packet
+struct PacketReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> PacketReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], anyhow::Error> {
+        if self.data.len() < n {
+            return Err(anyhow!("Truncated sftp packet"));
+        }
+        let (head, tail) = self.data.split_at(n);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, anyhow::Error> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, anyhow::Error> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, anyhow::Error> {
+        Ok(u64::from_be_bytes(self.take(8)?.try_into()?))
+    }
+
+    fn string(&mut self) -> Result<&'a [u8], anyhow::Error> {
+        let len = self.u32()? as usize;
+        self.take(len)
+    }
+}
@@ -0,0 +1,653 @@
+use anyhow::anyhow;
+use futures::stream::TryStreamExt;
+use openssh_sftp_client::UnixTimeStamp;
+use openssh_sftp_client::metadata::MetaDataBuilder;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::fs;
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+use crate::client::SftpClient;
+use crate::filter::FileFilter;
+use crate::operations::{download, stat, upload, walk};
+use crate::types::{
+    FileMetadata, FileTransferOperationResult, FileType, SftpError, SyncAction, SyncDirection,
+    SyncOptions, SyncReason, SyncReport, WalkOptions,
+};
+use crate::utils::{local_sha256, remote_sha256};
+
+/// Directory tree snapshot keyed by the path relative to the tree root.
+/// `BTreeMap` ordering guarantees parents are visited before their children.
+type Tree = BTreeMap<PathBuf, FileMetadata>;
+
+/// Mirrors a directory tree from source to destination
+///
+/// This function implements a one-way, rsync-like mirror:
+/// 1. Snapshots the local and remote trees (sizes and modification times)
+/// 2. Plans the actions needed to make the destination match the source
+/// 3. Creates missing directories and transfers new or changed files using `put`/`get`
+/// 4. Optionally deletes destination entries which do not exist on the source
+///    (entries rejected by `options.filter` are neither transferred nor deleted,
+///    and directories which still contain such entries are kept)
+/// 5. Copies the source modification time onto transferred files so that
+///    unchanged files are skipped on the next run
+///
+/// # Arguments
+///
+/// * `client` - The SFTP client instance
+/// * `local_dir` - Path to the local directory
+/// * `remote_dir` - Path to the remote directory
+/// * `direction` - Whether to mirror local to remote or remote to local
+/// * `options` - Sync options (delete, dry run, checksum comparison, filter)
+/// * `cancel_token` - Token for cancelling the sync operation
+///
+/// # Returns
+///
+/// Returns a `SyncReport` with the performed (or, on dry run, planned) actions
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The source directory does not exist or cannot be read
+/// - A file exists on one side where the other side has a directory
+/// - Any directory creation, transfer or deletion fails
+pub async fn sync(
+    client: &SftpClient,
+    local_dir: String,
+    remote_dir: String,
+    direction: SyncDirection,
+    options: SyncOptions,
+    cancel_token: CancellationToken,
+) -> Result<SyncReport, anyhow::Error> {
+    let filter = options.filter.as_ref();
+    let local_tree = local_tree(&local_dir, filter).await?;
+    let mut report = SyncReport {
+        actions: Vec::new(),
+        dry_run: options.dry_run,
+        cancelled: false,
+    };
+    let remote_tree = match remote_tree(client, &remote_dir, filter, &cancel_token).await {
+        Ok(tree) => tree,
+        Err(e) if e.downcast_ref::<SftpError>() == Some(&SftpError::Cancelled) => {
+            info!("sync operation cancelled by user");
+            report.cancelled = true;
+            return Ok(report);
+        }
+        Err(e) => return Err(e),
+    };
+
+    let (src_root, dest_root, src_tree, dest_tree) = match direction {
+        SyncDirection::Upload => (&local_dir, &remote_dir, &local_tree, &remote_tree),
+        SyncDirection::Download => (&remote_dir, &local_dir, &remote_tree, &local_tree),
+    };
+    // A missing destination is created, but a missing source must not be
+    // mirrored as an empty tree, which would delete the whole destination
+    if !src_tree.contains_key(Path::new("")) {
+        return Err(match direction {
+            SyncDirection::Upload => anyhow!("Local directory {:?} does not exist", local_dir),
+            SyncDirection::Download => SftpError::NotFound { path: remote_dir }.into(),
+        });
+    }
+    let partial_dirs = if options.delete {
+        partial_dirs(client, direction, dest_root, src_tree, dest_tree).await?
+    } else {
+        HashSet::new()
+    };
+    let mut actions = plan(
+        src_root,
+        dest_root,
+        src_tree,
+        dest_tree,
+        &partial_dirs,
+        &options,
+    )?;
+    if options.checksum {
+        actions = discard_unchanged(client, direction, actions).await?;
+    }
+
+    if options.dry_run {
+        for action in &actions {
+            info!("[dry-run] {}", action);
+        }
+        report.actions = actions;
+        return Ok(report);
+    }
+
+    for action in actions {
+        if cancel_token.is_cancelled() {
+            info!("sync operation cancelled by user");
+            report.cancelled = true;
+            break;
+        }
+        info!("{}", action);
+        match &action {
+            SyncAction::CreateDir { path } => match direction {
+                SyncDirection::Upload => client.sftp.fs().create_dir(path).await?,
+                SyncDirection::Download => fs::create_dir_all(path).await?,
+            },
+            SyncAction::Transfer {
+                src_file,
+                dest_file,
+                ..
+            } => {
+                let result = match direction {
+                    SyncDirection::Upload => {
+                        upload::put(
+                            client,
+                            src_file.clone(),
+                            dest_file.clone(),
+                            cancel_token.clone(),
+                        )
+                        .await?
+                    }
+                    SyncDirection::Download => {
+                        download::get(
+                            client,
+                            src_file.clone(),
+                            dest_file.clone(),
+                            cancel_token.clone(),
+                        )
+                        .await?
+                    }
+                };
+                if let FileTransferOperationResult::Cancelled { .. } = result {
+                    report.cancelled = true;
+                    break;
+                }
+                let relative = Path::new(src_file)
+                    .strip_prefix(src_root)
+                    .unwrap_or(Path::new(src_file));
+                if let Some(modified) = src_tree.get(relative).and_then(|e| e.last_modified_at) {
+                    set_modified(client, direction, dest_file, modified).await?;
+                }
+            }
+            SyncAction::Delete { path, file_type } => match (direction, file_type) {
+                (SyncDirection::Upload, FileType::Directory) => {
+                    client.sftp.fs().remove_dir(path).await?
+                }
+                (SyncDirection::Upload, FileType::Regular | FileType::Symlink) => {
+                    client.sftp.fs().remove_file(path).await?
+                }
+                (SyncDirection::Download, FileType::Directory) => fs::remove_dir(path).await?,
+                (SyncDirection::Download, FileType::Regular | FileType::Symlink) => {
+                    fs::remove_file(path).await?
+                }
+            },
+        }
+        report.actions.push(action);
+    }
+
+    Ok(report)
+}
+
+/// Computes the actions needed to make the destination tree match the source tree
+///
+/// Directories in `partial_dirs`, and their parents, are not deleted because
+/// they still contain entries which are not part of the snapshot. With
+/// `options.checksum`, files of equal size are planned as `ChecksumChanged`
+/// candidates which `discard_unchanged` filters afterwards.
+fn plan(
+    src_root: &str,
+    dest_root: &str,
+    src_tree: &Tree,
+    dest_tree: &Tree,
+    partial_dirs: &HashSet<PathBuf>,
+    options: &SyncOptions,
+) -> Result<Vec<SyncAction>, anyhow::Error> {
+    let mut actions = Vec::new();
+    let join = |root: &str, relative: &Path| -> String {
+        if relative.as_os_str().is_empty() {
+            root.to_string()
+        } else {
+            PathBuf::from(root)
+                .join(relative)
+                .to_string_lossy()
+                .into_owned()
+        }
+    };
+
+    for (relative, src) in src_tree {
+        let dest = dest_tree.get(relative);
+        if let Some(dest) = dest
+            && dest.file_type != src.file_type
+        {
+            return Err(anyhow!(
+                "Cannot sync {:?}: source is a {:?} but destination is a {:?}",
+                relative,
+                src.file_type,
+                dest.file_type
+            ));
+        }
+
+        match src.file_type {
+            // Symlinks are not part of the snapshots
+            FileType::Symlink => {}
+            FileType::Directory => {
+                if dest.is_none() {
+                    actions.push(SyncAction::CreateDir {
+                        path: join(dest_root, relative),
+                    });
+                }
+            }
+            FileType::Regular => {
+                let src_file = join(src_root, relative);
+                let dest_file = join(dest_root, relative);
+                let reason = match dest {
+                    None => Some(SyncReason::Missing),
+                    Some(dest) if dest.size != src.size => Some(SyncReason::SizeChanged),
+                    Some(_) if options.checksum => Some(SyncReason::ChecksumChanged),
+                    Some(dest)
+                        if unix_secs(dest.last_modified_at) != unix_secs(src.last_modified_at) =>
+                    {
+                        Some(SyncReason::ModifiedTimeChanged)
+                    }
+                    Some(_) => None,
+                };
+                if let Some(reason) = reason {
+                    actions.push(SyncAction::Transfer {
+                        src_file,
+                        dest_file,
+                        reason,
+                    });
+                }
+            }
+        }
+    }
+
+    if options.delete {
+        // Reverse order so that children are deleted before their parents
+        let mut kept = partial_dirs.clone();
+        for (relative, dest) in dest_tree.iter().rev() {
+            // The destination root itself is never deleted
+            if relative.as_os_str().is_empty() || src_tree.contains_key(relative) {
+                continue;
+            }
+            if kept.contains(relative) {
+                info!(
+                    "Keeping {:?}: it contains entries excluded from the sync",
+                    relative
+                );
+                kept.extend(relative.parent().map(Path::to_path_buf));
+                continue;
+            }
+            actions.push(SyncAction::Delete {
+                path: join(dest_root, relative),
+                file_type: dest.file_type,
+            });
+        }
+    }
+
+    Ok(actions)
+}
+
+/// Drops the `ChecksumChanged` candidates whose source and destination
+/// checksums are equal
+async fn discard_unchanged(
+    client: &SftpClient,
+    direction: SyncDirection,
+    actions: Vec<SyncAction>,
+) -> Result<Vec<SyncAction>, anyhow::Error> {
+    let io_size = client.config.io_size;
+    let mut changed = Vec::with_capacity(actions.len());
+    for action in actions {
+        if let SyncAction::Transfer {
+            src_file,
+            dest_file,
+            reason: SyncReason::ChecksumChanged,
+        } = &action
+        {
+            let (local_file, remote_file) = match direction {
+                SyncDirection::Upload => (src_file, dest_file),
+                SyncDirection::Download => (dest_file, src_file),
+            };
+            let local_sum = local_sha256(local_file, io_size).await?;
+            let remote_sum = remote_sha256(&client.sftp, remote_file, io_size).await?;
+            if local_sum == remote_sum {
+                continue;
+            }
+        }
+        changed.push(action);
+    }
+    Ok(changed)
+}
+
+/// Finds the destination directories to delete which contain more entries
+/// than their snapshot: entries rejected by the filter, symlinks or special
+/// files. Removing such a directory would fail.
+async fn partial_dirs(
+    client: &SftpClient,
+    direction: SyncDirection,
+    dest_root: &str,
+    src_tree: &Tree,
+    dest_tree: &Tree,
+) -> Result<HashSet<PathBuf>, anyhow::Error> {
+    let mut snapshot_children: HashMap<&Path, usize> = HashMap::new();
+    for relative in dest_tree.keys() {
+        if let Some(parent) = relative.parent() {
+            *snapshot_children.entry(parent).or_default() += 1;
+        }
+    }
+
+    let mut partial = HashSet::new();
+    for (relative, dest) in dest_tree {
+        if dest.file_type != FileType::Directory
+            || relative.as_os_str().is_empty()
+            || src_tree.contains_key(relative)
+        {
+            continue;
+        }
+        let path = Path::new(dest_root).join(relative);
+        let children = match direction {
+            SyncDirection::Upload => {
+                let dir = client.sftp.fs().open_dir(&path).await?;
+                let entries = dir.read_dir();
+                futures::pin_mut!(entries);
+                let mut count = 0;
+                while let Some(entry) = entries.try_next().await? {
+                    let name = entry.filename();
+                    if name != Path::new(".") && name != Path::new("..") {
+                        count += 1;
+                    }
+                }
+                count
+            }
+            SyncDirection::Download => {
+                let mut entries = fs::read_dir(&path).await?;
+                let mut count = 0;
+                while entries.next_entry().await?.is_some() {
+                    count += 1;
+                }
+                count
+            }
+        };
+        if children
+            > snapshot_children
+                .get(relative.as_path())
+                .copied()
+                .unwrap_or(0)
+        {
+            partial.insert(relative.clone());
+        }
+    }
+    Ok(partial)
+}
+
+/// Snapshots a local directory tree. A missing root results in an empty tree.
+async fn local_tree(root: &str, filter: Option<&FileFilter>) -> Result<Tree, anyhow::Error> {
+    let mut tree = Tree::new();
+    match fs::metadata(root).await {
+        Ok(metadata) if metadata.is_dir() => {}
+        Ok(_) => return Err(anyhow!("Local path {:?} is not a directory", root)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(tree),
+        Err(e) => return Err(e.into()),
+    }
+    tree.insert(PathBuf::new(), directory_entry(PathBuf::from(root)));
+
+    let mut pending = vec![PathBuf::new()];
+    while let Some(relative) = pending.pop() {
+        let mut entries = fs::read_dir(Path::new(root).join(&relative)).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let file_type = entry.file_type().await?;
+            let child = relative.join(entry.file_name());
+            let file = if file_type.is_dir() {
+                directory_entry(entry.path())
+            } else if file_type.is_file() {
+                let metadata = entry.metadata().await?;
+                FileMetadata {
+                    path: entry.path(),
+                    size: Some(metadata.len()),
+                    file_type: FileType::Regular,
+                    last_accessed_at: metadata.accessed().ok(),
+                    last_modified_at: metadata.modified().ok(),
+                    permissions: None,
+                    uid: None,
+                    gid: None,
+                    link_target: None,
+                }
+            } else {
+                continue;
+            };
+            if filter.is_some_and(|f| !f.matches_relative(&file, &child)) {
+                continue;
+            }
+            if file.file_type == FileType::Directory {
+                pending.push(child.clone());
+            }
+            tree.insert(child, file);
+        }
+    }
+    Ok(tree)
+}
+
+/// Snapshots a remote directory tree. A missing root results in an empty tree.
+async fn remote_tree(
+    client: &SftpClient,
+    root: &str,
+    filter: Option<&FileFilter>,
+    cancel_token: &CancellationToken,
+) -> Result<Tree, anyhow::Error> {
+    let mut tree = Tree::new();
+    match stat::try_stat(client, root.to_string()).await? {
+        Some(metadata) if metadata.file_type == FileType::Directory => {}
+        Some(_) => return Err(anyhow!("Remote path {:?} is not a directory", root)),
+        None => return Ok(tree),
+    }
+    tree.insert(PathBuf::new(), directory_entry(PathBuf::from(root)));
+
+    let options = WalkOptions {
+        filter: filter.cloned(),
+        ..Default::default()
+    };
+    let entries = walk::walk(client, root.to_string(), options, cancel_token.clone());
+    futures::pin_mut!(entries);
+    while let Some(entry) = entries.try_next().await? {
+        // Symlinks are not synced
+        if entry.metadata.file_type == FileType::Symlink {
+            continue;
+        }
+        if let Ok(relative) = entry.metadata.path.strip_prefix(root) {
+            tree.insert(relative.to_path_buf(), entry.metadata);
+        }
+    }
+    Ok(tree)
+}
+
+/// Copies the source modification time onto a transferred destination file
+async fn set_modified(
+    client: &SftpClient,
+    direction: SyncDirection,
+    dest_file: &str,
+    modified: SystemTime,
+) -> Result<(), anyhow::Error> {
+    match direction {
+        SyncDirection::Upload => {
+            let timestamp = UnixTimeStamp::new(modified)?;
+            client
+                .sftp
+                .fs()
+                .set_metadata(
+                    dest_file,
+                    MetaDataBuilder::new().time(timestamp, timestamp).create(),
+                )
+                .await?;
+        }
+        SyncDirection::Download => {
+            let file = fs::File::options().write(true).open(dest_file).await?;
+            file.into_std().await.set_modified(modified)?;
+        }
+    }
+    Ok(())
+}
+
+fn directory_entry(path: PathBuf) -> FileMetadata {
+    FileMetadata {
+        path,
+        size: None,
+        file_type: FileType::Directory,
+        last_accessed_at: None,
+        last_modified_at: None,
+        permissions: None,
+        uid: None,
+        gid: None,
+        link_target: None,
+    }
+}
+
+/// SFTP v3 timestamps only have second precision
+fn unix_secs(time: Option<SystemTime>) -> Option<u64> {
+    time.and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn file(path: &str, size: u64, modified: u64) -> (PathBuf, FileMetadata) {
+        let metadata = FileMetadata {
+            path: PathBuf::from(path),
+            size: Some(size),
+            file_type: FileType::Regular,
+            last_accessed_at: None,
+            last_modified_at: Some(UNIX_EPOCH + Duration::from_secs(modified)),
+            permissions: None,
+            uid: None,
+            gid: None,
+            link_target: None,
+        };
+        (PathBuf::from(path), metadata)
+    }
+
+    fn dir(path: &str) -> (PathBuf, FileMetadata) {
+        (PathBuf::from(path), directory_entry(PathBuf::from(path)))
+    }
+
+    fn plan_strings(
+        src: Tree,
+        dest: Tree,
+        partial_dirs: &[&str],
+        options: SyncOptions,
+    ) -> Vec<String> {
+        let partial_dirs = partial_dirs.iter().map(PathBuf::from).collect();
+        plan("/src", "/dest", &src, &dest, &partial_dirs, &options)
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn plans_missing_and_changed_files() {
+        let src = Tree::from([
+            dir(""),
+            dir("a"),
+            file("a/new", 1, 10),
+            file("same", 5, 10),
+            file("grown", 6, 10),
+            file("touched", 5, 20),
+        ]);
+        let dest = Tree::from([
+            dir(""),
+            file("same", 5, 10),
+            file("grown", 5, 10),
+            file("touched", 5, 10),
+        ]);
+        assert_eq!(
+            plan_strings(src, dest, &[], SyncOptions::default()),
+            [
+                "mkdir /dest/a",
+                "copy /src/a/new -> /dest/a/new (new)",
+                "copy /src/grown -> /dest/grown (size changed)",
+                "copy /src/touched -> /dest/touched (mtime changed)",
+            ]
+        );
+    }
+
+    #[test]
+    fn checksum_mode_plans_candidates_regardless_of_mtime() {
+        let src = Tree::from([dir(""), file("same", 5, 10), file("touched", 5, 20)]);
+        let dest = Tree::from([dir(""), file("same", 5, 10), file("touched", 5, 10)]);
+        let options = SyncOptions {
+            checksum: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            plan_strings(src, dest, &[], options),
+            [
+                "copy /src/same -> /dest/same (checksum changed)",
+                "copy /src/touched -> /dest/touched (checksum changed)",
+            ]
+        );
+    }
+
+    #[test]
+    fn deletes_children_before_parents_and_never_the_root() {
+        let src = Tree::from([dir(""), file("keep", 1, 10)]);
+        let dest = Tree::from([
+            dir(""),
+            file("keep", 1, 10),
+            dir("old"),
+            file("old/file", 1, 10),
+            dir("old/sub"),
+            file("old-file", 1, 10),
+        ]);
+        let options = SyncOptions {
+            delete: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            plan_strings(src, dest, &[], options),
+            [
+                "delete /dest/old-file",
+                "delete /dest/old/sub",
+                "delete /dest/old/file",
+                "delete /dest/old",
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_partial_dirs_and_their_parents() {
+        let src = Tree::from([dir("")]);
+        let dest = Tree::from([
+            dir(""),
+            dir("a"),
+            dir("a/partial"),
+            file("a/partial/file", 1, 10),
+            dir("b"),
+        ]);
+        let options = SyncOptions {
+            delete: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            plan_strings(src, dest, &["a/partial"], options),
+            ["delete /dest/b", "delete /dest/a/partial/file"]
+        );
+    }
+
+    #[test]
+    fn without_delete_extraneous_entries_are_kept() {
+        let src = Tree::from([dir("")]);
+        let dest = Tree::from([dir(""), file("extra", 1, 10)]);
+        assert!(plan_strings(src, dest, &[], SyncOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn rejects_type_conflicts() {
+        let src = Tree::from([dir(""), dir("a")]);
+        let dest = Tree::from([dir(""), file("a", 1, 10)]);
+        let result = plan(
+            "/src",
+            "/dest",
+            &src,
+            &dest,
+            &HashSet::new(),
+            &SyncOptions::default(),
+        );
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,188 @@
+use anyhow::anyhow;
+use bytes::BytesMut;
+use futures::stream::{self, Stream};
+use openssh_sftp_client::file::File;
+use std::io::SeekFrom;
+use tokio::io::AsyncSeekExt;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::client::SftpClient;
+use crate::types::{SftpError, TailEvent, TailOptions};
+
+struct TailState<'a> {
+    client: &'a SftpClient,
+    remote_path: String,
+    options: TailOptions,
+    cancel_token: CancellationToken,
+    file: Option<File>,
+    /// Whether the path and the open handle reported different sizes at the
+    /// previous check, with no data read since
+    size_mismatch: bool,
+    /// Whether the path disappeared while following the open handle
+    path_missing: bool,
+    done: bool,
+}
+
+/// Follows a growing remote file, like `tail -f`
+///
+/// The file is read until its end, then polled every `poll_interval` for
+/// new data. SFTP does not expose inode numbers, so rotation is detected by
+/// comparing the size reported for the path with the size of the open handle:
+/// 1. If the handle shrank below the current position, the file was truncated
+///    and reading resumes from its start
+/// 2. If the path disappeared and reappeared, reports a size below the
+///    current position, or reported a different size than the drained handle
+///    on two consecutive checks without any data read in between, it refers
+///    to a new file, which is opened and read from its start
+///
+/// Appends between the two size queries make the sizes differ on a busy
+/// file, so a single mismatch, or mismatches separated by reads, are ignored.
+///
+/// # Arguments
+///
+/// * `client` - The SFTP client instance
+/// * `remote_path` - Path to the remote file to follow
+/// * `options` - Poll interval and starting position
+/// * `cancel_token` - Token for stopping the tail
+///
+/// # Returns
+///
+/// Returns a stream of `TailEvent`, which only ends with an error
+///
+/// # Errors
+///
+/// The stream yields an error and ends if:
+/// - The remote file cannot be opened initially, or read
+/// - The operation is cancelled by the user (`SftpError::Cancelled`)
+pub fn tail(
+    client: &SftpClient,
+    remote_path: String,
+    options: TailOptions,
+    cancel_token: CancellationToken,
+) -> impl Stream<Item = Result<TailEvent, anyhow::Error>> + '_ {
+    let state = TailState {
+        client,
+        remote_path,
+        options,
+        cancel_token,
+        file: None,
+        size_mismatch: false,
+        path_missing: false,
+        done: false,
+    };
+
+    stream::unfold(state, |mut state| async move {
+        if state.done {
+            return None;
+        }
+        if state.file.is_none()
+            && let Err(e) = open(&mut state).await
+        {
+            state.done = true;
+            return Some((Err(e), state));
+        }
+
+        loop {
+            let io_size = state.client.config.io_size;
+            let file = state.file.as_mut()?;
+            let read_result = tokio::select! {
+                _ = state.cancel_token.cancelled() => None,
+                read_result = file.read(io_size as u32, BytesMut::with_capacity(io_size)) => {
+                    Some(read_result)
+                }
+            };
+            let event = match read_result {
+                None => {
+                    info!("tail operation cancelled by user");
+                    state.done = true;
+                    return Some((Err(SftpError::Cancelled.into()), state));
+                }
+                Some(Ok(Some(buf))) => {
+                    state.size_mismatch = false;
+                    Ok(Some(TailEvent::Data(buf.freeze())))
+                }
+                Some(Ok(None)) => check(&mut state).await,
+                Some(Err(e)) => Err(anyhow!("Error reading from remote file: {e}")),
+            };
+            match event {
+                Ok(Some(event)) => return Some((Ok(event), state)),
+                Ok(None) => {}
+                Err(e) => {
+                    state.done = true;
+                    return Some((Err(e), state));
+                }
+            }
+
+            tokio::select! {
+                _ = state.cancel_token.cancelled() => {}
+                _ = tokio::time::sleep(state.options.poll_interval) => {}
+            }
+        }
+    })
+}
+
+/// Opens the file to follow, positioned at its end unless reading from the start
+async fn open(state: &mut TailState<'_>) -> Result<(), anyhow::Error> {
+    let mut file = state.client.sftp.open(state.remote_path.clone()).await?;
+    info!("Remote file opened: {:?}", state.remote_path);
+    if !state.options.from_start {
+        let size = file.metadata().await?.len().unwrap_or(0);
+        file.seek(SeekFrom::Start(size)).await?;
+    }
+    state.file = Some(file);
+    Ok(())
+}
+
+/// Checks a fully read file for truncation and rotation
+async fn check(state: &mut TailState<'_>) -> Result<Option<TailEvent>, anyhow::Error> {
+    let Some(file) = state.file.as_mut() else {
+        return Ok(None);
+    };
+    let handle_size = file.metadata().await?.len();
+    if let Some(handle_size) = handle_size
+        && handle_size < file.offset()
+    {
+        warn!("Remote file {:?} truncated", state.remote_path);
+        file.seek(SeekFrom::Start(0)).await?;
+        state.size_mismatch = false;
+        return Ok(Some(TailEvent::Truncated));
+    }
+
+    let path_size = match state.client.sftp.fs().metadata(&state.remote_path).await {
+        Ok(metadata) => metadata.len(),
+        Err(e) => {
+            if !state.path_missing {
+                warn!("Remote file {:?} is gone: {}", state.remote_path, e);
+            }
+            state.path_missing = true;
+            return Ok(None);
+        }
+    };
+    // The path can only be smaller than what was read from the handle if it
+    // refers to another file
+    let shrunk = path_size.is_some_and(|size| size < file.offset());
+    let mismatch = path_size != handle_size;
+    let rotated = state.path_missing || shrunk || (mismatch && state.size_mismatch);
+    state.size_mismatch = mismatch;
+    if !rotated {
+        return Ok(None);
+    }
+
+    // The new file may not be readable yet, keep following the old one until it is
+    let new_file = match state.client.sftp.open(state.remote_path.clone()).await {
+        Ok(new_file) => new_file,
+        Err(e) => {
+            warn!(
+                "Failed to reopen rotated file {:?}: {}",
+                state.remote_path, e
+            );
+            return Ok(None);
+        }
+    };
+    warn!("Remote file {:?} rotated", state.remote_path);
+    state.file = Some(new_file);
+    state.path_missing = false;
+    state.size_mismatch = false;
+    Ok(Some(TailEvent::Rotated))
+}
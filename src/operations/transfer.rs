@@ -0,0 +1,122 @@
+use std::io::SeekFrom;
+use tokio::io::AsyncSeekExt;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+
+use crate::client::SftpClient;
+use crate::operations::download;
+use crate::remote_file::RemoteFile;
+use crate::types::{FileTransferOperationResult, SftpError, TransferOptions};
+use crate::utils::remote_sha256;
+
+/// Transfers a file from one remote server to another
+///
+/// The file is streamed from `src_client` into `dest_client` through this
+/// process without touching local disk, using the concurrent read and
+/// ordered write logic of `get`:
+/// 1. With `options.resume`, a destination file smaller than the source is
+///    treated as a partial copy and only the missing bytes are transferred
+/// 2. Progress updates are sent to `options.progress` after every chunk
+/// 3. With `options.verify_checksum`, the SHA-256 checksums of both files are
+///    compared once the transfer completes
+///
+/// # Arguments
+///
+/// * `src_client` - Client connected to the source server
+/// * `src_path` - Path of the file on the source server
+/// * `dest_client` - Client connected to the destination server
+/// * `dest_path` - Destination path on the destination server
+/// * `options` - Resume, checksum and progress options
+/// * `cancel_token` - Token for cancelling the transfer
+///
+/// # Returns
+///
+/// Returns a `FileTransferOperationResult`:
+/// - `Completed` with transfer progress if successful
+/// - `Cancelled` if the operation was cancelled; the partial destination file
+///   is kept so the transfer can be resumed
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The source file cannot be opened or read
+/// - The destination file cannot be created, written to or closed
+/// - The checksums differ (`SftpError::ChecksumMismatch`)
+/// - An SFTP error occurs during transfer
+pub async fn transfer(
+    src_client: &SftpClient,
+    src_path: String,
+    dest_client: &SftpClient,
+    dest_path: String,
+    options: TransferOptions,
+    cancel_token: CancellationToken,
+) -> Result<FileTransferOperationResult, anyhow::Error> {
+    let mut src_file = src_client.sftp.open(src_path.clone()).await?;
+    info!("Source file opened: {:?}", src_path);
+    let src_size = src_file.metadata().await?.len();
+
+    let mut offset = 0;
+    if options.resume
+        && let Ok(existing) = dest_client.sftp.fs().metadata(&dest_path).await
+        && let (Some(existing), Some(src_size)) = (existing.len(), src_size)
+        && existing <= src_size
+    {
+        offset = existing;
+    }
+
+    let dest_file = if offset > 0 {
+        let mut dest_file = dest_client
+            .sftp
+            .options()
+            .write(true)
+            .open(&dest_path)
+            .await?;
+        dest_file.seek(SeekFrom::Start(offset)).await?;
+        src_file.seek(SeekFrom::Start(offset)).await?;
+        info!("Resuming transfer to {:?} at byte {}", dest_path, offset);
+        dest_file
+    } else {
+        let dest_file = dest_client.sftp.create(dest_path.clone()).await?;
+        info!("Destination file created: {:?}", dest_path);
+        dest_file
+    };
+
+    let mut dest = RemoteFile::from_file(dest_client, dest_path.clone(), dest_file);
+    let result = download::download(
+        src_client,
+        src_file,
+        src_path.clone(),
+        None,
+        &mut dest,
+        dest_path.clone(),
+        options.progress,
+        cancel_token,
+    )
+    .await;
+    // Closed before verifying so that a failed write or close is reported as
+    // such rather than as a checksum mismatch
+    let closed = dest.close().await;
+    let result = result?;
+    closed?;
+
+    if options.verify_checksum && matches!(result, FileTransferOperationResult::Completed(_)) {
+        let (src_checksum, dest_checksum) = tokio::try_join!(
+            remote_sha256(&src_client.sftp, &src_path, src_client.config.io_size),
+            remote_sha256(&dest_client.sftp, &dest_path, dest_client.config.io_size),
+        )?;
+        if src_checksum != dest_checksum {
+            error!(
+                "Checksum mismatch between {:?} and {:?}",
+                src_path, dest_path
+            );
+            return Err(SftpError::ChecksumMismatch {
+                src_file: src_path,
+                dest_file: dest_path,
+            }
+            .into());
+        }
+        info!("Checksum of {:?} verified", dest_path);
+    }
+
+    Ok(result)
+}
@@ -1,15 +1,17 @@
 use anyhow::anyhow;
 use futures::stream::{FuturesUnordered, StreamExt};
 use std::collections::HashMap;
+use std::path::Path;
 use std::time::Instant;
 use tokio::fs;
-use tokio::io::{self, AsyncReadExt, AsyncWriteExt};
+use tokio::io::{self, AsyncRead, AsyncReadExt};
 use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
 use tracing::{error, info, warn};
 
 use crate::client::SftpClient;
-use crate::types::{FileTransferOperationResult, FileTransferProgress};
+use crate::operations::{stat, statvfs};
+use crate::types::{FileTransferOperationResult, FileTransferProgress, FileType, SftpError};
 
 /// Uploads a local file to the remote server
 ///
@@ -36,6 +38,9 @@ use crate::types::{FileTransferOperationResult, FileTransferProgress};
 ///
 /// Returns an error if:
 /// - The local file cannot be opened or read
+/// - The remote path is a directory
+/// - `check_disk_space` is enabled and the destination lacks room
+///   (`SftpError::InsufficientSpace`)
 /// - The remote file cannot be created or written to
 /// - There's a channel communication error
 /// - An SFTP error occurs during transfer
@@ -45,12 +50,115 @@ pub async fn put(
     remote_path: String,
     cancel_token: CancellationToken,
 ) -> Result<FileTransferOperationResult, anyhow::Error> {
-    let upload_time = Instant::now();
-    let mut is_cancelled: bool = false;
-    let mut local_file = fs::File::open(local_path.clone()).await?;
+    let local_file = fs::File::open(local_path.clone()).await?;
     info!("Local file opened: {:?}", local_path);
     let local_file_size = local_file.metadata().await?.len();
 
+    upload(
+        client,
+        local_file,
+        local_path,
+        remote_path,
+        Some(local_file_size),
+        cancel_token,
+    )
+    .await
+}
+
+/// Uploads data read from an `AsyncRead` source to the remote server
+///
+/// Uses the same concurrent chunking and cancellation logic as [`put`], so
+/// data produced on the fly (generated reports, HTTP response bodies,
+/// decompressed streams) can be uploaded without staging it on disk.
+///
+/// # Arguments
+///
+/// * `client` - The SFTP client instance
+/// * `reader` - Source of the data to upload, read until end of file
+/// * `remote_path` - Destination path on the remote server
+/// * `size_hint` - Expected number of bytes, used for the disk space check
+/// * `cancel_token` - Token for cancelling the upload operation
+///
+/// # Returns
+///
+/// Returns a `FileTransferOperationResult`:
+/// - `Completed` with the number of bytes actually uploaded as `file_size`
+/// - `Cancelled` if the operation was cancelled
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The reader returns an error
+/// - The remote path is a directory
+/// - `check_disk_space` is enabled, `size_hint` is set and the destination
+///   lacks room (`SftpError::InsufficientSpace`)
+/// - The remote file cannot be created or written to
+/// - There's a channel communication error
+/// - An SFTP error occurs during transfer
+pub async fn put_from_reader(
+    client: &SftpClient,
+    reader: impl AsyncRead,
+    remote_path: String,
+    size_hint: Option<u64>,
+    cancel_token: CancellationToken,
+) -> Result<FileTransferOperationResult, anyhow::Error> {
+    upload(
+        client,
+        reader,
+        READER_SOURCE.to_string(),
+        remote_path,
+        size_hint,
+        cancel_token,
+    )
+    .await
+}
+
+/// Source name reported in the results of `put_from_reader`
+const READER_SOURCE: &str = "<reader>";
+
+/// Uploads everything read from `reader` to `remote_path`
+///
+/// `src_file` is only used for logging and in the returned result.
+async fn upload(
+    client: &SftpClient,
+    reader: impl AsyncRead,
+    src_file: String,
+    remote_path: String,
+    size_hint: Option<u64>,
+    cancel_token: CancellationToken,
+) -> Result<FileTransferOperationResult, anyhow::Error> {
+    let upload_time = Instant::now();
+    let mut is_cancelled: bool = false;
+    let mut bytes_uploaded: u64 = 0;
+    tokio::pin!(reader);
+
+    if let Some(existing) = stat::try_stat(client, remote_path.clone()).await?
+        && existing.file_type == FileType::Directory
+    {
+        return Err(anyhow!("Remote path {:?} is a directory", remote_path));
+    }
+
+    if client.config.check_disk_space
+        && let Some(required) = size_hint
+    {
+        let remote_dir = match Path::new(&remote_path).parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_string_lossy().into_owned(),
+            _ => ".".to_string(),
+        };
+        let space = statvfs::disk_space(client, remote_dir).await?;
+        if space.available_bytes < required {
+            error!(
+                "Not enough space for {:?}: {} bytes required, {} bytes available",
+                remote_path, required, space.available_bytes
+            );
+            return Err(SftpError::InsufficientSpace {
+                required,
+                available: space.available_bytes,
+            }
+            .into());
+        }
+    }
+
     let (tx, mut rx) = mpsc::channel::<(usize, Vec<u8>)>(client.config.concurrency);
 
     let mut buffer_idx = 0;
@@ -62,7 +170,7 @@ pub async fn put(
             remote_path.clone(),
             err
         );
-        io::Error::new(io::ErrorKind::Other, format!("SFTP error: {:?}", err))
+        io::Error::other(format!("SFTP error: {:?}", err))
     })?;
     info!("Remote file created path: {:?}", remote_path.clone());
 
@@ -82,7 +190,7 @@ pub async fn put(
             buffer_map.insert(idx, buffer);
             while let Some(buffer) = buffer_map.remove(&current_idx) {
                 remote_file.write_all(&buffer).await.map_err(|e| {
-                    io::Error::new(io::ErrorKind::Other, format!("SFTP write error: {:?}", e))
+                    io::Error::other(format!("SFTP write error: {:?}", e))
                 })?;
                 let _bytes_written = buffer.len() as u64;
                 current_idx += 1;
@@ -91,7 +199,7 @@ pub async fn put(
         Ok::<(), anyhow::Error>(())
     });
 
-    // Task to read from local file
+    // Task to read from the source
     let mut upload_error: Option<anyhow::Error> = None;
     loop {
         let mut buffer = vec![0; client.config.io_size];
@@ -102,7 +210,7 @@ pub async fn put(
                 stop_transmission.cancel();
                 break;
             }
-            read_result = local_file.read(&mut buffer[..]) => {
+            read_result = reader.read(&mut buffer[..]) => {
                 let bytes_read = match read_result {
                     Ok(0) => {
                         info!("Upload: End of file reached");
@@ -110,11 +218,12 @@ pub async fn put(
                     },
                     Ok(n) => n,
                     Err(e) => {
-                        error!("Error reading local file: {:?}", e);
-                        upload_error = Some(anyhow!("Failed to read from remote file: {e}"));
+                        error!("Error reading {:?}: {:?}", src_file, e);
+                        upload_error = Some(anyhow!("Failed to read from {src_file}: {e}"));
                         break;
                     }
                 };
+                bytes_uploaded += bytes_read as u64;
                 let tx = tx.clone();
                 let buffer = buffer[..bytes_read].to_vec();
                 tasks.push(tokio::spawn(async move {
@@ -145,20 +254,21 @@ pub async fn put(
     let time_taken = upload_time.elapsed();
     info!(
         "File {:?} uploaded. Time taken {:?}",
-        local_path, time_taken,
+        src_file, time_taken,
     );
 
     if is_cancelled {
         Ok(FileTransferOperationResult::Cancelled {
-            src_file: local_path.clone(),
-            dest_file: remote_path.clone(),
+            src_file,
+            dest_file: remote_path,
         })
     } else {
         Ok(FileTransferOperationResult::Completed(
             FileTransferProgress {
-                src_file: local_path.clone(),
-                dest_file: remote_path.clone(),
-                file_size: local_file_size,
+                src_file,
+                dest_file: remote_path,
+                file_size: bytes_uploaded,
+                bytes_transferred: bytes_uploaded,
                 percentage_progress: 100.0_f64,
             },
         ))
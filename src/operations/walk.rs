@@ -0,0 +1,228 @@
+use futures::future::BoxFuture;
+use futures::stream::{self, FuturesOrdered, Stream, StreamExt};
+use std::collections::{HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::client::SftpClient;
+use crate::operations::list;
+use crate::types::{FileMetadata, FileType, SftpError, WalkEntry, WalkOptions};
+use crate::utils::file_metadata;
+
+/// Result of reading one directory: its depth, canonical path (only resolved
+/// when following symlinks) and entries
+type DirListing = (
+    usize,
+    Option<PathBuf>,
+    Result<Vec<FileMetadata>, anyhow::Error>,
+);
+
+struct WalkState<'a> {
+    client: &'a SftpClient,
+    root: PathBuf,
+    options: WalkOptions,
+    concurrency: usize,
+    queue: VecDeque<(PathBuf, usize)>,
+    in_flight: FuturesOrdered<BoxFuture<'a, DirListing>>,
+    ready: VecDeque<Result<WalkEntry, anyhow::Error>>,
+    visited: HashSet<PathBuf>,
+    cancel_token: CancellationToken,
+    done: bool,
+}
+
+/// Recursively walks a remote directory tree
+///
+/// This function implements a concurrent breadth-first traversal:
+/// 1. Up to `concurrency` directories are read at the same time
+/// 2. Results are yielded in discovery order, so the output is deterministic
+///    and a parent directory is always yielded before its contents
+/// 3. Entries rejected by `options.filter` are skipped and excluded
+///    directories are not descended into
+/// 4. When following symlinks, every directory is canonicalized and only
+///    traversed once, which breaks symlink loops
+///
+/// The root itself is not yielded; its direct children have depth 1.
+///
+/// # Arguments
+///
+/// * `client` - The SFTP client instance
+/// * `root` - Path to the remote directory to walk
+/// * `options` - Walk options (max depth, symlinks, sorting, concurrency, filter)
+/// * `cancel_token` - Token for cancelling the walk
+///
+/// # Returns
+///
+/// Returns a stream of `WalkEntry` for every regular file, directory and
+/// (when not following symlinks) symlink below `root`
+///
+/// # Errors
+///
+/// The stream yields an error, then continues with the remaining directories,
+/// if a directory cannot be opened or read. Broken symlinks are yielded as
+/// `FileType::Symlink` entries when following symlinks.
+///
+/// The stream yields `SftpError::Cancelled` and ends if the walk is cancelled.
+pub fn walk(
+    client: &SftpClient,
+    root: String,
+    options: WalkOptions,
+    cancel_token: CancellationToken,
+) -> impl Stream<Item = Result<WalkEntry, anyhow::Error>> + '_ {
+    let concurrency = options
+        .concurrency
+        .unwrap_or(client.config.concurrency)
+        .max(1);
+    let root = PathBuf::from(root);
+    let state = WalkState {
+        client,
+        queue: VecDeque::from([(root.clone(), 0)]),
+        root,
+        options,
+        concurrency,
+        in_flight: FuturesOrdered::new(),
+        ready: VecDeque::new(),
+        visited: HashSet::new(),
+        cancel_token,
+        done: false,
+    };
+
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(entry) = state.ready.pop_front() {
+                return Some((entry, state));
+            }
+            if state.done {
+                return None;
+            }
+
+            while state.in_flight.len() < state.concurrency {
+                let Some((dir, depth)) = state.queue.pop_front() else {
+                    break;
+                };
+                let future = read_dir(
+                    state.client,
+                    dir,
+                    depth,
+                    state.options.follow_symlinks,
+                    state.cancel_token.clone(),
+                );
+                state.in_flight.push_back(Box::pin(future));
+            }
+
+            let listing = tokio::select! {
+                _ = state.cancel_token.cancelled() => None,
+                listing = state.in_flight.next() => match listing {
+                    Some(listing) => Some(listing),
+                    // Nothing in flight and nothing queued: the walk is complete
+                    None => return None,
+                },
+            };
+            match listing {
+                Some(listing) => process(&mut state, listing),
+                None => {
+                    info!("walk operation cancelled by user");
+                    state.done = true;
+                    return Some((Err(SftpError::Cancelled.into()), state));
+                }
+            }
+        }
+    })
+}
+
+/// Reads one directory, resolving symlink targets when following symlinks
+async fn read_dir(
+    client: &SftpClient,
+    dir: PathBuf,
+    depth: usize,
+    follow_symlinks: bool,
+    cancel_token: CancellationToken,
+) -> DirListing {
+    let canonical = if follow_symlinks {
+        match client.sftp.fs().canonicalize(&dir).await {
+            Ok(canonical) => Some(canonical),
+            Err(e) => return (depth, None, Err(e.into())),
+        }
+    } else {
+        None
+    };
+
+    let entries = list::read_dir(client, dir.to_string_lossy().into_owned(), cancel_token).await;
+    let entries = match entries {
+        Ok(entries) if follow_symlinks => Ok(resolve_symlinks(client, entries).await),
+        entries => entries,
+    };
+    (depth, canonical, entries)
+}
+
+/// Replaces the metadata of symlinks with the metadata of their targets.
+/// Broken symlinks are kept as `FileType::Symlink`.
+async fn resolve_symlinks(
+    client: &SftpClient,
+    mut entries: Vec<FileMetadata>,
+) -> Vec<FileMetadata> {
+    for entry in entries
+        .iter_mut()
+        .filter(|e| e.file_type == FileType::Symlink)
+    {
+        match client.sftp.fs().metadata(&entry.path).await {
+            Ok(target) if target.file_type().is_some_and(|t| t.is_dir() || t.is_file()) => {
+                let link_target = entry.link_target.take();
+                *entry = file_metadata(entry.path.clone(), &target);
+                entry.link_target = link_target;
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Failed to resolve symlink {:?}: {}", entry.path, e),
+        }
+    }
+    entries
+}
+
+/// Queues the sub-directories of a finished listing and buffers its entries
+fn process(state: &mut WalkState<'_>, (depth, canonical, entries): DirListing) {
+    let mut entries = match entries {
+        Ok(entries) => entries,
+        Err(e) => {
+            if e.downcast_ref::<SftpError>() == Some(&SftpError::Cancelled) {
+                info!("walk operation cancelled by user");
+                state.done = true;
+            }
+            state.ready.push_back(Err(e));
+            return;
+        }
+    };
+    if let Some(canonical) = canonical
+        && !state.visited.insert(canonical.clone())
+    {
+        warn!("Skipping already visited directory {:?}", canonical);
+        return;
+    }
+    if state.options.sort {
+        entries.sort_by(|a, b| a.path.file_name().cmp(&b.path.file_name()));
+    }
+
+    let depth = depth + 1;
+    if state.options.max_depth.is_some_and(|max| depth > max) {
+        return;
+    }
+    for entry in entries {
+        if let Some(filter) = &state.options.filter {
+            let relative = entry
+                .path
+                .strip_prefix(&state.root)
+                .unwrap_or(Path::new(""));
+            if !filter.matches_relative(&entry, relative) {
+                continue;
+            }
+        }
+        let descend = entry.file_type == FileType::Directory
+            && state.options.max_depth.is_none_or(|max| depth < max);
+        if descend {
+            state.queue.push_back((entry.path.clone(), depth));
+        }
+        state.ready.push_back(Ok(WalkEntry {
+            metadata: entry,
+            depth,
+        }));
+    }
+}
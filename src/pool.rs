@@ -0,0 +1,379 @@
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Instant;
+use tokio::sync::Notify;
+use tracing::{debug, info, warn};
+
+use crate::client::SftpClient;
+use crate::session::SftpSessionManager;
+use crate::types::{ConnectOptions, SessionKey, SessionPoolConfig, SftpError};
+
+/// Usage of a pooled session, shared with the clients leased from it
+#[derive(Debug)]
+struct Usage {
+    /// Number of `PooledClient`s currently using the session
+    active: AtomicUsize,
+    /// When the last `PooledClient` of the session was dropped
+    last_used: Mutex<Instant>,
+}
+
+impl Usage {
+    fn active(&self) -> usize {
+        self.active.load(Ordering::Acquire)
+    }
+
+    fn idle_since(&self) -> Option<Instant> {
+        (self.active() == 0).then(|| *self.last_used.lock().expect("usage lock poisoned"))
+    }
+}
+
+/// Open SSH session and the SFTP client created from it
+#[derive(Debug)]
+struct PooledSession {
+    manager: SftpSessionManager,
+    client: Arc<SftpClient>,
+    usage: Arc<Usage>,
+}
+
+impl PooledSession {
+    fn lease(&self) -> PooledClient {
+        self.usage.active.fetch_add(1, Ordering::AcqRel);
+        PooledClient {
+            client: self.client.clone(),
+            usage: self.usage.clone(),
+        }
+    }
+
+    /// Closes the client and the session. Only called once no `PooledClient`
+    /// uses the session anymore.
+    async fn close(self, key: &SessionKey) {
+        match Arc::try_unwrap(self.client) {
+            Ok(client) => {
+                if let Err(e) = client.close().await {
+                    warn!("Failed to close sftp client of {:?}: {:#}", key, e);
+                }
+            }
+            Err(_) => warn!("Sftp client of {:?} is still in use", key),
+        }
+        let mut manager = self.manager;
+        if let Err(e) = manager.close().await {
+            warn!("Failed to close ssh session to {:?}: {:#}", key, e);
+        }
+    }
+}
+
+/// Sessions of one key
+#[derive(Debug, Default)]
+struct KeySessions {
+    state: Mutex<KeyState>,
+    /// Notified whenever a reserved slot is released
+    released: Notify,
+}
+
+/// Sessions and reserved slots of one key, only locked for bookkeeping
+#[derive(Debug, Default)]
+struct KeyState {
+    /// Open sessions which are not being health checked
+    sessions: Vec<PooledSession>,
+    /// Slots reserved by sessions being opened or health checked, which count
+    /// towards `max_sessions_per_key`
+    reserved: usize,
+}
+
+impl KeySessions {
+    fn lock(&self) -> MutexGuard<'_, KeyState> {
+        self.state.lock().expect("pool lock poisoned")
+    }
+}
+
+/// Slot reserved for a session being opened or health checked, released on drop
+struct Reservation {
+    sessions: Arc<KeySessions>,
+}
+
+impl Reservation {
+    /// Adds the session to the pool and leases it
+    fn fill(self, session: PooledSession) -> PooledClient {
+        let client = session.lease();
+        self.sessions.lock().sessions.push(session);
+        client
+    }
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        self.sessions.lock().reserved -= 1;
+        self.sessions.released.notify_waiters();
+    }
+}
+
+/// Next step of `SessionPool::get`, decided while the key is locked
+enum Step {
+    Lease(PooledClient),
+    Check(Reservation, PooledSession),
+    Connect(Reservation),
+    Wait,
+}
+
+/// Pool of SSH sessions keyed by host, port and user
+///
+/// Sessions are opened lazily on the first `get` for a key. An SFTP session
+/// multiplexes concurrent requests, so callers share sessions: a new session
+/// is only opened when every session of the key is busy and fewer than
+/// `max_sessions_per_key` are open, otherwise the least busy one is returned.
+///
+/// Before an idle session is handed out again, its health is checked with
+/// `session.check()` and a dead session is replaced by a new one. Sessions
+/// unused for `idle_timeout` are closed by `evict_idle`, which also runs for
+/// the requested key on every `get`. Sessions are opened and health checked
+/// without holding any lock, so a slow or unreachable host only delays the
+/// callers waiting for that session.
+///
+/// # Example
+///
+/// ```ignore
+/// let pool = SessionPool::new(SessionPoolConfig {
+///     max_sessions_per_key: 2,
+///     idle_timeout: Duration::from_secs(60),
+///     ..SessionPoolConfig::default()
+/// });
+/// let key = SessionKey {
+///     host: "example.com".to_string(),
+///     port: None,
+///     user: "user".to_string(),
+/// };
+/// let client = pool.get(&key).await?;
+/// let files = client.ls("/data".to_string(), CancellationToken::new()).await?;
+/// ```
+#[derive(Debug)]
+pub struct SessionPool {
+    config: SessionPoolConfig,
+    sessions: Mutex<HashMap<SessionKey, Arc<KeySessions>>>,
+}
+
+/// SFTP client leased from a `SessionPool`
+///
+/// Dereferences to `SftpClient`. The session is marked as unused when the
+/// last `PooledClient` leased from it is dropped.
+#[derive(Debug)]
+pub struct PooledClient {
+    client: Arc<SftpClient>,
+    usage: Arc<Usage>,
+}
+
+impl Deref for PooledClient {
+    type Target = SftpClient;
+
+    fn deref(&self) -> &SftpClient {
+        &self.client
+    }
+}
+
+impl Drop for PooledClient {
+    fn drop(&mut self) {
+        *self.usage.last_used.lock().expect("usage lock poisoned") = Instant::now();
+        self.usage.active.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl SessionPool {
+    /// Creates an empty pool; no connection is opened until `get` is called
+    pub fn new(config: SessionPoolConfig) -> Self {
+        Self {
+            config,
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns a client connected to `key`
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Host, port and user to connect to
+    ///
+    /// # Returns
+    ///
+    /// Returns a `PooledClient` sharing a healthy pooled session, opening a
+    /// new session if needed
+    ///
+    /// # Errors
+    ///
+    /// Returns `SftpError::ConnectionFailed` if a new session is needed and
+    /// cannot be opened
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let client = pool.get(&key).await?;
+    /// client.put(local, remote, cancel_token).await?;
+    /// ```
+    pub async fn get(&self, key: &SessionKey) -> Result<PooledClient, anyhow::Error> {
+        let sessions = self.sessions_of(key);
+        loop {
+            // Registered before checking the state so that no release is missed
+            let released = sessions.released.notified();
+            let (expired, step) = {
+                let mut state = sessions.lock();
+                let expired = self.take_expired(&mut state.sessions);
+                let step = self.next_step(&sessions, &mut state);
+                (expired, step)
+            };
+            for session in expired {
+                debug!("Closing idle session to {:?}", key);
+                session.close(key).await;
+            }
+
+            match step {
+                Step::Lease(client) => return Ok(client),
+                Step::Check(reservation, mut session) => {
+                    if session.manager.connected().await {
+                        return Ok(reservation.fill(session));
+                    }
+                    warn!("Pooled session to {:?} is dead, discarding it", key);
+                    drop(reservation);
+                    session.close(key).await;
+                }
+                Step::Connect(reservation) => {
+                    let session = self.connect(key).await?;
+                    return Ok(reservation.fill(session));
+                }
+                Step::Wait => released.await,
+            }
+        }
+    }
+
+    /// Closes every session unused for longer than `idle_timeout`
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of closed sessions
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// // Evict idle sessions every minute
+    /// tokio::spawn(async move {
+    ///     let mut interval = tokio::time::interval(Duration::from_secs(60));
+    ///     loop {
+    ///         interval.tick().await;
+    ///         pool.evict_idle().await;
+    ///     }
+    /// });
+    /// ```
+    pub async fn evict_idle(&self) -> usize {
+        let mut evicted = 0;
+        for (key, sessions) in self.all_sessions() {
+            let expired = self.take_expired(&mut sessions.lock().sessions);
+            evicted += expired.len();
+            for session in expired {
+                debug!("Closing idle session to {:?}", key);
+                session.close(&key).await;
+            }
+        }
+        evicted
+    }
+
+    /// Closes every session which is not in use
+    ///
+    /// Sessions still used by a `PooledClient` are left open.
+    pub async fn close(&self) {
+        for (key, sessions) in self.all_sessions() {
+            let (idle, busy) = {
+                let mut state = sessions.lock();
+                let (idle, busy): (Vec<_>, Vec<_>) = state
+                    .sessions
+                    .drain(..)
+                    .partition(|session| session.usage.active() == 0);
+                state.sessions = busy;
+                (idle, state.sessions.len())
+            };
+            for session in idle {
+                session.close(&key).await;
+            }
+            if busy > 0 {
+                warn!("{} sessions to {:?} are still in use", busy, key);
+            }
+        }
+    }
+
+    /// Picks the session to lease or check, or reserves a slot to connect
+    fn next_step(&self, sessions: &Arc<KeySessions>, state: &mut KeyState) -> Step {
+        let least_busy = state
+            .sessions
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, session)| session.usage.active())
+            .map(|(index, session)| (index, session.usage.active()));
+        let full = state.sessions.len() + state.reserved >= self.config.max_sessions_per_key.max(1);
+        let mut reserve = || {
+            state.reserved += 1;
+            Reservation {
+                sessions: sessions.clone(),
+            }
+        };
+        match least_busy {
+            // Busy sessions are in use, so only idle ones need a health check
+            Some((index, 0)) => {
+                let reservation = reserve();
+                Step::Check(reservation, state.sessions.swap_remove(index))
+            }
+            Some((index, _)) if full => Step::Lease(state.sessions[index].lease()),
+            None if full => Step::Wait,
+            _ => Step::Connect(reserve()),
+        }
+    }
+
+    fn sessions_of(&self, key: &SessionKey) -> Arc<KeySessions> {
+        let mut sessions = self.sessions.lock().expect("pool lock poisoned");
+        sessions.entry(key.clone()).or_default().clone()
+    }
+
+    fn all_sessions(&self) -> Vec<(SessionKey, Arc<KeySessions>)> {
+        let sessions = self.sessions.lock().expect("pool lock poisoned");
+        sessions
+            .iter()
+            .map(|(key, sessions)| (key.clone(), sessions.clone()))
+            .collect()
+    }
+
+    /// Removes the sessions of one key unused for longer than `idle_timeout`
+    fn take_expired(&self, sessions: &mut Vec<PooledSession>) -> Vec<PooledSession> {
+        let (expired, kept): (Vec<_>, Vec<_>) = sessions.drain(..).partition(|session| {
+            session
+                .usage
+                .idle_since()
+                .is_some_and(|since| since.elapsed() >= self.config.idle_timeout)
+        });
+        *sessions = kept;
+        expired
+    }
+
+    async fn connect(&self, key: &SessionKey) -> Result<PooledSession, anyhow::Error> {
+        info!("Opening pooled session to {:?}", key);
+        let connection_failed = |reason: String| SftpError::ConnectionFailed {
+            host: key.host.clone(),
+            reason,
+        };
+        let options = ConnectOptions {
+            port: key.port.or(self.config.connect_options.port),
+            ..self.config.connect_options.clone()
+        };
+        let manager = SftpSessionManager::connect_with_options(&key.host, &key.user, options)
+            .await
+            .map_err(|e| connection_failed(e.to_string()))?;
+        let client = manager
+            .create_sftp_client(self.config.client_config.clone())
+            .await
+            .map_err(|e| connection_failed(format!("{:#}", e)))?;
+        Ok(PooledSession {
+            manager,
+            client: Arc::new(client),
+            usage: Arc::new(Usage {
+                active: AtomicUsize::new(0),
+                last_used: Mutex::new(Instant::now()),
+            }),
+        })
+    }
+}
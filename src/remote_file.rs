@@ -0,0 +1,272 @@
+use bytes::{Bytes, BytesMut};
+use openssh_sftp_client::Error as SftpError;
+use openssh_sftp_client::file::File;
+use openssh_sftp_client::metadata::MetaData;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+use tokio::io::{AsyncRead, AsyncSeek, AsyncSeekExt, AsyncWrite, AsyncWriteExt, ReadBuf};
+use tracing::info;
+
+use crate::client::SftpClient;
+use crate::operations::attributes;
+use crate::types::{FileAttributes, FileMetadata, OpenOptions};
+use crate::utils::file_metadata;
+
+/// Handle to an open remote file supporting random access
+///
+/// Implements `AsyncRead`, `AsyncWrite` and `AsyncSeek`, so it can be used
+/// with the `tokio::io` extension traits and utilities such as
+/// `tokio::io::copy`. Writes are buffered and sent asynchronously; call
+/// `flush` or `close` to make sure they reached the server.
+pub struct RemoteFile {
+    path: PathBuf,
+    /// Handle of the file; its offset is the current position
+    file: File,
+    /// Maximum number of bytes requested by a single read
+    io_size: usize,
+    /// Read in progress, running on a clone of `file`
+    pending_read: Option<ReadFuture>,
+    /// Writes sent but not yet acknowledged, oldest first, each running on a
+    /// clone of `file`
+    pending_writes: VecDeque<WriteFuture>,
+    /// Maximum number of writes awaiting acknowledgement
+    max_pending_writes: usize,
+    /// Size query in progress for a `SeekFrom::End` seek, with the requested offset
+    pending_end_seek: Option<(i64, MetadataFuture)>,
+}
+
+type ReadFuture = Pin<Box<dyn Future<Output = Result<Option<BytesMut>, SftpError>> + Send>>;
+type WriteFuture = Pin<Box<dyn Future<Output = Result<(), SftpError>> + Send>>;
+type MetadataFuture = Pin<Box<dyn Future<Output = Result<MetaData, SftpError>> + Send>>;
+
+impl std::fmt::Debug for RemoteFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RemoteFile")
+            .field("path", &self.path)
+            .field("offset", &self.file.offset())
+            .finish()
+    }
+}
+
+impl RemoteFile {
+    /// Opens a remote file (internal use)
+    pub(crate) async fn open(
+        client: &SftpClient,
+        remote_path: String,
+        options: OpenOptions,
+    ) -> Result<Self, anyhow::Error> {
+        let file = client
+            .sftp
+            .options()
+            .read(options.read)
+            .write(options.write || options.append)
+            .append(options.append)
+            .create(options.create)
+            .truncate(options.truncate)
+            .create_new(options.exclusive)
+            .open(&remote_path)
+            .await?;
+        info!("Remote file opened: {:?} ({:?})", remote_path, options);
+
+        let mut remote_file = Self::from_file(client, remote_path, file);
+        if options.append {
+            // Servers are not required to honour the append flag, so start at the end
+            remote_file.seek(io::SeekFrom::End(0)).await?;
+        }
+        Ok(remote_file)
+    }
+
+    /// Wraps a file already opened by an operation (internal use)
+    pub(crate) fn from_file(client: &SftpClient, remote_path: String, file: File) -> Self {
+        Self {
+            path: PathBuf::from(remote_path),
+            file,
+            io_size: client.config.io_size.max(1),
+            pending_read: None,
+            pending_writes: VecDeque::new(),
+            max_pending_writes: client.config.concurrency.max(1),
+            pending_end_seek: None,
+        }
+    }
+
+    /// Path the file was opened with
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    /// Queries the metadata of the open file
+    pub async fn metadata(&mut self) -> Result<FileMetadata, anyhow::Error> {
+        let metadata = self.file.metadata().await?;
+        Ok(file_metadata(self.path.clone(), &metadata))
+    }
+
+    /// Changes attributes of the open file (fsetstat); `None` fields are left unchanged
+    pub async fn set_attributes(
+        &mut self,
+        attributes: FileAttributes,
+    ) -> Result<(), anyhow::Error> {
+        self.flush().await?;
+        let file = &mut self.file;
+        let current = if attributes::needs_current(&attributes) {
+            Some(file.metadata().await?)
+        } else {
+            None
+        };
+        let metadata = attributes::to_metadata(&attributes, current.as_ref())?;
+        file.set_metadata(metadata).await?;
+        Ok(())
+    }
+
+    /// Truncates or extends the file to `size` bytes
+    pub async fn set_len(&mut self, size: u64) -> Result<(), anyhow::Error> {
+        self.flush().await?;
+        self.file.set_len(size).await?;
+        Ok(())
+    }
+
+    /// Flushes pending writes and asks the server to persist the file to disk
+    /// (requires the `fsync@openssh.com` extension)
+    pub async fn sync_all(&mut self) -> Result<(), anyhow::Error> {
+        self.flush().await?;
+        self.file.sync_all().await?;
+        Ok(())
+    }
+
+    /// Flushes pending writes and closes the file, reporting any error
+    /// returned by the server for the close request
+    pub async fn close(mut self) -> Result<(), anyhow::Error> {
+        self.flush().await?;
+        // The handle is only closed once its last clone is released
+        self.pending_read = None;
+        self.pending_end_seek = None;
+        self.file.close().await?;
+        Ok(())
+    }
+
+    /// Waits for the acknowledgement of the oldest writes until at most
+    /// `max` are pending
+    fn poll_pending_writes(&mut self, cx: &mut Context<'_>, max: usize) -> Poll<io::Result<()>> {
+        while self.pending_writes.len() > max {
+            let write = self.pending_writes.front_mut().expect("pending write");
+            let result = ready!(write.as_mut().poll(cx));
+            self.pending_writes.pop_front();
+            result.map_err(io::Error::other)?;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    /// Moves the position of the file forward by `n` bytes
+    fn advance(&mut self, n: usize) -> io::Result<()> {
+        Pin::new(&mut self.file).start_seek(io::SeekFrom::Current(n as i64))
+    }
+}
+
+impl AsyncRead for RemoteFile {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_pending_writes(cx, 0))?;
+        if buf.remaining() == 0 {
+            return Poll::Ready(Ok(()));
+        }
+
+        let read = this.pending_read.get_or_insert_with(|| {
+            let mut file = this.file.clone();
+            let len = buf.remaining().min(this.io_size);
+            Box::pin(async move { file.read(len as u32, BytesMut::with_capacity(len)).await })
+        });
+        let data = ready!(read.as_mut().poll(cx));
+        this.pending_read = None;
+        // `None` is the end of the file
+        if let Some(data) = data.map_err(io::Error::other)? {
+            let n = data.len().min(buf.remaining());
+            buf.put_slice(&data[..n]);
+            this.advance(n)?;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for RemoteFile {
+    /// Sends the data and returns without waiting for the server to
+    /// acknowledge it, unless too many writes are already pending; a failed
+    /// write is reported by a later call
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        ready!(this.poll_pending_writes(cx, this.max_pending_writes - 1))?;
+        this.pending_read = None;
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        let mut file = this.file.clone();
+        let data = Bytes::copy_from_slice(buf);
+        let mut write: WriteFuture = Box::pin(async move { file.write_all(&data).await });
+        // Polled once to send the request right away
+        match write.as_mut().poll(cx) {
+            Poll::Ready(result) => result.map_err(io::Error::other)?,
+            Poll::Pending => this.pending_writes.push_back(write),
+        }
+        this.advance(buf.len())?;
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_pending_writes(cx, 0)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_pending_writes(cx, 0)
+    }
+}
+
+impl AsyncSeek for RemoteFile {
+    /// Seeking from the end queries the file size from the server, so pending
+    /// writes should be flushed first for them to be taken into account.
+    fn start_seek(self: Pin<&mut Self>, position: io::SeekFrom) -> io::Result<()> {
+        let this = self.get_mut();
+        this.pending_read = None;
+        match position {
+            io::SeekFrom::End(offset) => {
+                // The SFTP protocol has no seek request, the size has to be fetched
+                let mut file = this.file.clone();
+                let size = Box::pin(async move { file.metadata().await });
+                this.pending_end_seek = Some((offset, size));
+                Ok(())
+            }
+            position => {
+                this.pending_end_seek = None;
+                Pin::new(&mut this.file).start_seek(position)
+            }
+        }
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        let this = self.get_mut();
+        if let Some((offset, size)) = this.pending_end_seek.as_mut() {
+            let metadata = ready!(size.as_mut().poll(cx));
+            let offset = *offset;
+            this.pending_end_seek = None;
+            let metadata = metadata.map_err(io::Error::other)?;
+            let size = metadata
+                .len()
+                .ok_or_else(|| io::Error::other("Server did not report the file size"))?;
+            let position = size.checked_add_signed(offset).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, "Invalid seek offset")
+            })?;
+            Pin::new(&mut this.file).start_seek(io::SeekFrom::Start(position))?;
+        }
+        Poll::Ready(Ok(this.file.offset()))
+    }
+}
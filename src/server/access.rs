@@ -0,0 +1,190 @@
+use anyhow::anyhow;
+use axum::extract::{Request, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use rusty_sftp::SessionKey;
+
+use crate::error::ApiError;
+use crate::routes::AppState;
+
+/// Access control of the API
+///
+/// Every connection is opened with the key of the server, so whoever can send
+/// a request can act as any user that key is authorized for. Requests are
+/// limited to the servers matching the allow-list and, when a token is
+/// configured, to clients presenting it.
+#[derive(Debug)]
+pub(crate) struct Access {
+    /// Patterns matched against `user@host`
+    allowed: GlobSet,
+    /// Token expected in an `Authorization: Bearer` header
+    token: Option<String>,
+}
+
+impl Access {
+    /// Creates the access rules from `[user@]host` glob patterns, a pattern
+    /// without `user@` allowing every user of the matching hosts
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a pattern is not a valid glob.
+    pub(crate) fn new(patterns: &[String], token: Option<String>) -> Result<Self, anyhow::Error> {
+        let mut allowed = GlobSetBuilder::new();
+        for pattern in patterns {
+            let pattern = if pattern.contains('@') {
+                pattern.clone()
+            } else {
+                format!("*@{}", pattern)
+            };
+            let glob = Glob::new(&pattern)
+                .map_err(|e| anyhow!("Invalid host pattern {:?}: {}", pattern, e))?;
+            allowed.add(glob);
+        }
+        Ok(Self {
+            allowed: allowed.build()?,
+            token,
+        })
+    }
+
+    /// Checks that the server may connect to `key`
+    ///
+    /// # Errors
+    ///
+    /// Returns a `403 Forbidden` error if `key` does not match the allow-list,
+    /// if its host or user starts with `-` and could be taken for an `ssh`
+    /// option, or if its host contains a `@`.
+    pub(crate) fn check(&self, key: &SessionKey) -> Result<(), ApiError> {
+        let destination = format!("{}@{}", key.user, key.host);
+        let malformed =
+            key.host.starts_with('-') || key.user.starts_with('-') || key.host.contains('@');
+        if malformed || !self.allowed.is_match(&destination) {
+            return Err(ApiError::new(
+                StatusCode::FORBIDDEN,
+                anyhow!("connections to {} are not allowed", destination),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether the headers carry the expected bearer token, always true when
+    /// no token is configured
+    fn authorized(&self, headers: &HeaderMap) -> bool {
+        let Some(token) = &self.token else {
+            return true;
+        };
+        headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|given| constant_time_eq(given.as_bytes(), token.as_bytes()))
+    }
+}
+
+/// Middleware rejecting requests without the configured bearer token with
+/// `401 Unauthorized`
+pub(crate) async fn require_token(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if state.access.authorized(request.headers()) {
+        return next.run(request).await;
+    }
+    let error = anyhow!("missing or invalid bearer token");
+    let mut response = ApiError::new(StatusCode::UNAUTHORIZED, error).into_response();
+    response
+        .headers_mut()
+        .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+    response
+}
+
+/// Compares two byte strings in a time independent of where they differ
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(user: &str, host: &str) -> SessionKey {
+        SessionKey {
+            host: host.to_string(),
+            port: None,
+            user: user.to_string(),
+        }
+    }
+
+    fn patterns(patterns: &[&str]) -> Vec<String> {
+        patterns.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn allows_matching_hosts_only() {
+        let access = Access::new(&patterns(&["localhost", "*.internal"]), None).unwrap();
+        assert!(access.check(&key("alice", "localhost")).is_ok());
+        assert!(access.check(&key("bob", "files.internal")).is_ok());
+        assert!(access.check(&key("alice", "example.com")).is_err());
+        assert!(access.check(&key("alice", "internal")).is_err());
+    }
+
+    #[test]
+    fn user_patterns_restrict_users() {
+        let access = Access::new(&patterns(&["deploy@*.internal"]), None).unwrap();
+        assert!(access.check(&key("deploy", "files.internal")).is_ok());
+        assert!(access.check(&key("root", "files.internal")).is_err());
+    }
+
+    #[test]
+    fn empty_allow_list_rejects_everything() {
+        let access = Access::new(&[], None).unwrap();
+        assert!(access.check(&key("alice", "localhost")).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_destinations() {
+        let access = Access::new(&patterns(&["*"]), None).unwrap();
+        assert!(access.check(&key("alice", "any.host")).is_ok());
+        assert!(access.check(&key("alice", "-oProxyCommand=true")).is_err());
+        assert!(
+            access
+                .check(&key("-oProxyCommand=true", "any.host"))
+                .is_err()
+        );
+
+        let access = Access::new(&patterns(&["*@localhost"]), None).unwrap();
+        assert!(access.check(&key("alice", "root@localhost")).is_err());
+    }
+
+    #[test]
+    fn invalid_pattern_is_an_error() {
+        assert!(Access::new(&patterns(&["host[a-"]), None).is_err());
+    }
+
+    #[test]
+    fn checks_bearer_token() {
+        let access = Access::new(&[], Some("secret".to_string())).unwrap();
+        let mut headers = HeaderMap::new();
+        assert!(!access.authorized(&headers));
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer other"),
+        );
+        assert!(!access.authorized(&headers));
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Basic secret"),
+        );
+        assert!(!access.authorized(&headers));
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer secret"),
+        );
+        assert!(access.authorized(&headers));
+
+        let open = Access::new(&[], None).unwrap();
+        assert!(open.authorized(&HeaderMap::new()));
+    }
+}
@@ -0,0 +1,45 @@
+use axum::Json;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use rusty_sftp::SftpError;
+use serde_json::json;
+
+/// Error returned by a handler, rendered as a JSON body
+#[derive(Debug)]
+pub(crate) struct ApiError {
+    status: StatusCode,
+    error: anyhow::Error,
+}
+
+impl ApiError {
+    pub(crate) fn new(status: StatusCode, error: impl Into<anyhow::Error>) -> Self {
+        Self {
+            status,
+            error: error.into(),
+        }
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(error: anyhow::Error) -> Self {
+        let status = match error.downcast_ref::<SftpError>() {
+            Some(SftpError::ConnectionFailed { .. }) => StatusCode::BAD_GATEWAY,
+            Some(SftpError::NotFound { .. }) => StatusCode::NOT_FOUND,
+            Some(SftpError::Cancelled) => StatusCode::CONFLICT,
+            Some(SftpError::InsufficientSpace { .. }) => StatusCode::INSUFFICIENT_STORAGE,
+            Some(SftpError::FileTooLarge { .. }) => StatusCode::PAYLOAD_TOO_LARGE,
+            Some(SftpError::ChecksumMismatch { .. }) | None => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Self { status, error }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = json!({
+            "error": format!("{:#}", self.error),
+            "details": self.error.downcast_ref::<SftpError>(),
+        });
+        (self.status, Json(body)).into_response()
+    }
+}
@@ -0,0 +1,222 @@
+use rusty_sftp::{
+    CancellationToken, FileTransferOperationResult, FileTransferProgress, SessionKey, SessionPool,
+    SftpError, TransferOptions,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tracing::{debug, info};
+
+/// Capacity of the progress channel of a transfer; updates are dropped when full
+const PROGRESS_CHANNEL_SIZE: usize = 16;
+
+/// File on an SFTP server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Location {
+    #[serde(flatten)]
+    pub(crate) session_key: SessionKey,
+    pub(crate) path: String,
+}
+
+/// Body of `POST /jobs`: a file transfer between two servers (or two paths
+/// of the same server)
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct TransferRequest {
+    pub(crate) source: Location,
+    pub(crate) destination: Location,
+    /// Resume a partial copy at the destination
+    #[serde(default)]
+    pub(crate) resume: bool,
+    /// Compare SHA-256 checksums of both files after the transfer
+    #[serde(default)]
+    pub(crate) verify_checksum: bool,
+}
+
+/// State of a transfer job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum JobState {
+    Running,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+/// Status of a transfer job as returned by the API
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct JobStatus {
+    pub(crate) id: u64,
+    pub(crate) state: JobState,
+    pub(crate) source: Location,
+    pub(crate) destination: Location,
+    /// Latest progress reported by the transfer
+    pub(crate) progress: Option<FileTransferProgress>,
+    /// Final result of a completed or cancelled job
+    pub(crate) result: Option<FileTransferOperationResult>,
+    /// Error message of a failed job
+    pub(crate) error: Option<String>,
+}
+
+struct Job {
+    status: watch::Receiver<JobStatus>,
+    cancel_token: CancellationToken,
+}
+
+/// Registry of the transfer jobs submitted to the server
+///
+/// Finished jobs are removed `retention` after they end, so the registry
+/// does not grow with every transfer of a long-running server.
+pub(crate) struct Jobs {
+    next_id: AtomicU64,
+    jobs: Arc<Mutex<HashMap<u64, Job>>>,
+    retention: Duration,
+}
+
+impl Jobs {
+    /// Creates an empty registry keeping finished jobs for `retention`
+    pub(crate) fn new(retention: Duration) -> Self {
+        Self {
+            next_id: AtomicU64::new(0),
+            jobs: Arc::default(),
+            retention,
+        }
+    }
+
+    /// Starts a transfer in the background and returns its initial status
+    pub(crate) fn submit(&self, pool: Arc<SessionPool>, request: TransferRequest) -> JobStatus {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let status = JobStatus {
+            id,
+            state: JobState::Running,
+            source: request.source.clone(),
+            destination: request.destination.clone(),
+            progress: None,
+            result: None,
+            error: None,
+        };
+        let (status_tx, status_rx) = watch::channel(status.clone());
+        let cancel_token = CancellationToken::new();
+        self.jobs.lock().expect("jobs lock poisoned").insert(
+            id,
+            Job {
+                status: status_rx,
+                cancel_token: cancel_token.clone(),
+            },
+        );
+
+        let jobs = self.jobs.clone();
+        let retention = self.retention;
+        tokio::spawn(async move {
+            run(pool, request, status_tx, cancel_token).await;
+            tokio::time::sleep(retention).await;
+            debug!("Removing finished job {}", id);
+            jobs.lock().expect("jobs lock poisoned").remove(&id);
+        });
+        status
+    }
+
+    /// Returns the status of every job, oldest first
+    pub(crate) fn list(&self) -> Vec<JobStatus> {
+        let jobs = self.jobs.lock().expect("jobs lock poisoned");
+        let mut statuses: Vec<JobStatus> =
+            jobs.values().map(|j| j.status.borrow().clone()).collect();
+        statuses.sort_by_key(|status| status.id);
+        statuses
+    }
+
+    /// Returns the status of a job, or `None` if it does not exist
+    pub(crate) fn get(&self, id: u64) -> Option<JobStatus> {
+        let jobs = self.jobs.lock().expect("jobs lock poisoned");
+        jobs.get(&id).map(|job| job.status.borrow().clone())
+    }
+
+    /// Subscribes to the status updates of a job, or returns `None` if it
+    /// does not exist
+    pub(crate) fn subscribe(&self, id: u64) -> Option<watch::Receiver<JobStatus>> {
+        let jobs = self.jobs.lock().expect("jobs lock poisoned");
+        jobs.get(&id).map(|job| job.status.clone())
+    }
+
+    /// Requests the cancellation of a job and returns its current status, or
+    /// `None` if it does not exist
+    pub(crate) fn cancel(&self, id: u64) -> Option<JobStatus> {
+        let jobs = self.jobs.lock().expect("jobs lock poisoned");
+        let job = jobs.get(&id)?;
+        info!("Cancelling job {}", id);
+        job.cancel_token.cancel();
+        Some(job.status.borrow().clone())
+    }
+}
+
+/// Runs a transfer job, publishing its progress and final state
+async fn run(
+    pool: Arc<SessionPool>,
+    request: TransferRequest,
+    status: watch::Sender<JobStatus>,
+    cancel_token: CancellationToken,
+) {
+    let (progress_tx, mut progress_rx) = mpsc::channel(PROGRESS_CHANNEL_SIZE);
+    let transfer = transfer(&pool, &request, progress_tx, cancel_token);
+    // The channel closes when the transfer drops its sender
+    let forward_progress = async {
+        while let Some(progress) = progress_rx.recv().await {
+            status.send_modify(|status| status.progress = Some(progress));
+        }
+    };
+    let (result, ()) = tokio::join!(transfer, forward_progress);
+
+    status.send_modify(|status| match result {
+        Ok(FileTransferOperationResult::Completed(progress)) => {
+            status.state = JobState::Completed;
+            status.progress = Some(progress.clone());
+            status.result = Some(FileTransferOperationResult::Completed(progress));
+        }
+        Ok(result) => {
+            status.state = JobState::Cancelled;
+            status.result = Some(result);
+        }
+        Err(e) if e.downcast_ref::<SftpError>() == Some(&SftpError::Cancelled) => {
+            status.state = JobState::Cancelled;
+            status.result = Some(FileTransferOperationResult::Cancelled {
+                src_file: status.source.path.clone(),
+                dest_file: status.destination.path.clone(),
+            });
+        }
+        Err(e) => {
+            status.state = JobState::Failed;
+            status.error = Some(format!("{:#}", e));
+        }
+    });
+    info!(
+        "Job {} finished: {:?}",
+        status.borrow().id,
+        status.borrow().state
+    );
+}
+
+async fn transfer(
+    pool: &SessionPool,
+    request: &TransferRequest,
+    progress: mpsc::Sender<FileTransferProgress>,
+    cancel_token: CancellationToken,
+) -> Result<FileTransferOperationResult, anyhow::Error> {
+    let source = pool.get(&request.source.session_key).await?;
+    let destination = pool.get(&request.destination.session_key).await?;
+    let options = TransferOptions {
+        resume: request.resume,
+        verify_checksum: request.verify_checksum,
+        progress: Some(progress),
+    };
+    source
+        .transfer_to(
+            request.source.path.clone(),
+            &destination,
+            request.destination.path.clone(),
+            options,
+            cancel_token,
+        )
+        .await
+}
@@ -0,0 +1,175 @@
+/// Module for the access control of the API
+mod access;
+/// Module for mapping errors to HTTP responses
+mod error;
+/// Module for background transfer jobs
+mod jobs;
+/// Module for the HTTP routes
+mod routes;
+
+use anyhow::Context;
+use clap::Parser;
+use rusty_sftp::{
+    ConnectOptions, KnownHostsPolicy, SessionPool, SessionPoolConfig, SftpClientConfig,
+};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{Level, info};
+
+use crate::access::Access;
+use crate::jobs::Jobs;
+use crate::routes::AppState;
+
+/// REST API server exposing SFTP operations
+///
+/// Every file endpoint takes the server as `host`, `user` and optional `port`
+/// query parameters; sessions are opened on first use, shared between
+/// requests and closed after being idle for `--idle-timeout` seconds.
+///
+/// Connections use the key of the server, so anyone able to reach the listen
+/// address can act as any user that key is authorized for on the servers
+/// allowed with `--allow-host`. Keep the default loopback address or require
+/// a token with `--auth-token-file`.
+#[derive(Debug, Parser)]
+#[command(name = "rusty-sftp-server", version)]
+struct Args {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    listen: SocketAddr,
+
+    /// Servers the API may connect to, as `[user@]host` glob patterns, e.g.
+    /// `localhost` or `deploy@*.internal` (repeatable, `*` allows any server)
+    #[arg(long = "allow-host", value_name = "PATTERN", required = true)]
+    allowed_hosts: Vec<String>,
+
+    /// File holding a token that requests must send as `Authorization: Bearer <token>`
+    #[arg(long, value_name = "FILE")]
+    auth_token_file: Option<PathBuf>,
+
+    /// Private key file used for every connection
+    #[arg(short = 'i', long = "identity")]
+    ssh_key_path: Option<PathBuf>,
+
+    /// Host key checking policy (strict, add or accept)
+    #[arg(long, default_value = "add", value_parser = parse_known_hosts)]
+    known_hosts: KnownHostsPolicy,
+
+    /// Directory for SSH control sockets (defaults to the temporary directory)
+    #[arg(long)]
+    control_dir: Option<PathBuf>,
+
+    /// Connection timeout in seconds
+    #[arg(long, default_value_t = 60)]
+    connect_timeout: u64,
+
+    /// Buffer size for read/write operations in bytes
+    #[arg(long, default_value_t = 65_536)]
+    io_size: usize,
+
+    /// Number of concurrent operations per transfer
+    #[arg(long, default_value_t = 8)]
+    concurrency: usize,
+
+    /// Maximum number of sessions opened to the same host, port and user
+    #[arg(long, default_value_t = 4)]
+    max_sessions_per_key: usize,
+
+    /// Seconds after which an unused session is closed
+    #[arg(long, default_value_t = 300)]
+    idle_timeout: u64,
+
+    /// Seconds for which finished jobs are kept before being removed
+    #[arg(long, default_value_t = 3600)]
+    job_retention: u64,
+
+    /// Log more details (-v for debug, -vv for trace)
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+}
+
+fn parse_known_hosts(policy: &str) -> Result<KnownHostsPolicy, String> {
+    match policy {
+        "strict" => Ok(KnownHostsPolicy::Strict),
+        "add" => Ok(KnownHostsPolicy::Add),
+        "accept" => Ok(KnownHostsPolicy::Accept),
+        _ => Err(format!("expected strict, add or accept, got {:?}", policy)),
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), anyhow::Error> {
+    let args = Args::parse();
+    let level = match args.verbose {
+        0 => Level::INFO,
+        1 => Level::DEBUG,
+        _ => Level::TRACE,
+    };
+    tracing_subscriber::fmt().with_max_level(level).init();
+
+    let token = match &args.auth_token_file {
+        Some(path) => Some(read_token(path)?),
+        None => None,
+    };
+    let access = Access::new(&args.allowed_hosts, token)?;
+
+    let options = ConnectOptions {
+        port: None,
+        ssh_key_path: args.ssh_key_path,
+        control_dir: args.control_dir,
+        known_hosts: args.known_hosts,
+        connect_timeout: Duration::from_secs(args.connect_timeout),
+    };
+    let pool = Arc::new(SessionPool::new(SessionPoolConfig {
+        connect_options: options,
+        client_config: SftpClientConfig::new(args.io_size, args.concurrency),
+        max_sessions_per_key: args.max_sessions_per_key,
+        idle_timeout: Duration::from_secs(args.idle_timeout),
+    }));
+    let evictor = tokio::spawn(evict_idle(
+        pool.clone(),
+        Duration::from_secs(args.idle_timeout),
+    ));
+    let state = AppState {
+        pool: pool.clone(),
+        jobs: Arc::new(Jobs::new(Duration::from_secs(args.job_retention))),
+        access: Arc::new(access),
+    };
+
+    let listener = tokio::net::TcpListener::bind(args.listen).await?;
+    info!("Listening on {}", listener.local_addr()?);
+    axum::serve(listener, routes::router(state))
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+            info!("Shutting down");
+        })
+        .await?;
+
+    evictor.abort();
+    pool.close().await;
+    Ok(())
+}
+
+/// Reads the API token, ignoring surrounding whitespace
+fn read_token(path: &Path) -> Result<String, anyhow::Error> {
+    let token = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read token file {}", path.display()))?;
+    let token = token.trim();
+    if token.is_empty() {
+        anyhow::bail!("Token file {} is empty", path.display());
+    }
+    Ok(token.to_string())
+}
+
+/// Periodically closes the sessions which have been idle for too long
+async fn evict_idle(pool: Arc<SessionPool>, idle_timeout: Duration) {
+    let mut interval = tokio::time::interval(idle_timeout.max(Duration::from_secs(1)) / 2);
+    loop {
+        interval.tick().await;
+        let evicted = pool.evict_idle().await;
+        if evicted > 0 {
+            info!("Closed {} idle sessions", evicted);
+        }
+    }
+}
@@ -0,0 +1,298 @@
+use axum::body::Body;
+use axum::extract::{Path, Query, State};
+use axum::http::{StatusCode, header};
+use axum::middleware;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post, put};
+use axum::{Json, Router};
+use futures::stream::{self, Stream, TryStreamExt};
+use rusty_sftp::{
+    CancellationToken, FileMetadata, FileTransferOperationResult, FileType, PooledClient,
+    SessionKey, SessionPool, WalkOptions,
+};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use tokio_util::io::{ReaderStream, StreamReader};
+use tracing::warn;
+
+use crate::access::{self, Access};
+use crate::error::ApiError;
+use crate::jobs::{JobState, JobStatus, Jobs, TransferRequest};
+
+/// Size of the in-memory pipe between a download and its response body
+const DOWNLOAD_BUFFER_SIZE: usize = 256 * 1024;
+
+/// State shared by all handlers
+#[derive(Clone)]
+pub(crate) struct AppState {
+    pub(crate) pool: Arc<SessionPool>,
+    pub(crate) jobs: Arc<Jobs>,
+    pub(crate) access: Arc<Access>,
+}
+
+impl AppState {
+    /// Leases a session to `key` if the allow-list permits it
+    async fn client(&self, key: &SessionKey) -> Result<PooledClient, ApiError> {
+        self.access.check(key)?;
+        Ok(self.pool.get(key).await?)
+    }
+}
+
+/// Query parameters of the file endpoints
+#[derive(Debug, Deserialize)]
+struct FileQuery {
+    host: String,
+    user: String,
+    port: Option<u16>,
+    path: String,
+    /// `DELETE /files`: remove a directory and its contents
+    #[serde(default)]
+    recursive: bool,
+    /// `POST /mkdir`: create missing parent directories
+    #[serde(default)]
+    parents: bool,
+}
+
+impl FileQuery {
+    fn session_key(&self) -> SessionKey {
+        SessionKey {
+            host: self.host.clone(),
+            port: self.port,
+            user: self.user.clone(),
+        }
+    }
+}
+
+/// Routes of the API
+///
+/// The server connects with its own key to whichever server a request names,
+/// so only the servers allowed by `Access` are reachable and, when a token is
+/// configured, every request must carry it.
+pub(crate) fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/files", get(list).delete(remove))
+        .route("/stat", get(stat))
+        .route("/mkdir", post(mkdir))
+        .route("/download", get(download))
+        .route("/upload", put(upload))
+        .route("/jobs", get(list_jobs).post(submit_job))
+        .route("/jobs/{id}", get(job).delete(cancel_job))
+        .route("/jobs/{id}/events", get(job_events))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            access::require_token,
+        ))
+        .with_state(state)
+}
+
+/// `GET /files`: metadata of the entries of a directory, sorted by name
+async fn list(
+    State(state): State<AppState>,
+    Query(query): Query<FileQuery>,
+) -> Result<Json<Vec<FileMetadata>>, ApiError> {
+    let client = state.client(&query.session_key()).await?;
+    let options = WalkOptions {
+        max_depth: Some(1),
+        sort: true,
+        ..WalkOptions::default()
+    };
+    let entries = client.walk(query.path, options, CancellationToken::new());
+    let files = entries.map_ok(|entry| entry.metadata).try_collect().await?;
+    Ok(Json(files))
+}
+
+/// `GET /stat`: metadata of a path, following symlinks
+async fn stat(
+    State(state): State<AppState>,
+    Query(query): Query<FileQuery>,
+) -> Result<Json<FileMetadata>, ApiError> {
+    let client = state.client(&query.session_key()).await?;
+    Ok(Json(client.stat(query.path).await?))
+}
+
+/// `DELETE /files`: removes a file, an empty directory, or with
+/// `recursive=true` a directory tree
+async fn remove(
+    State(state): State<AppState>,
+    Query(query): Query<FileQuery>,
+) -> Result<StatusCode, ApiError> {
+    let client = state.client(&query.session_key()).await?;
+    let file = client.lstat(query.path.clone()).await?;
+    match file.file_type {
+        FileType::Directory if query.recursive => {
+            client
+                .remove_dir_all(query.path, CancellationToken::new())
+                .await?
+        }
+        FileType::Directory => client.remove_dir(query.path).await?,
+        FileType::Regular | FileType::Symlink => client.remove_file(query.path).await?,
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `POST /mkdir`: creates a directory, with `parents=true` including its
+/// missing parents
+async fn mkdir(
+    State(state): State<AppState>,
+    Query(query): Query<FileQuery>,
+) -> Result<StatusCode, ApiError> {
+    let client = state.client(&query.session_key()).await?;
+    if query.parents {
+        client.create_dir_all(query.path).await?;
+    } else {
+        client.create_dir(query.path).await?;
+    }
+    Ok(StatusCode::CREATED)
+}
+
+/// `GET /download`: streams the contents of a file
+async fn download(
+    State(state): State<AppState>,
+    Query(query): Query<FileQuery>,
+) -> Result<Response, ApiError> {
+    let client = state.client(&query.session_key()).await?;
+    let file = client.stat(query.path.clone()).await?;
+    if file.file_type == FileType::Directory {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            anyhow::anyhow!("{}: is a directory", query.path),
+        ));
+    }
+
+    // The download writes into one end of a pipe while the response body
+    // streams the other end; it fails and stops once the client disconnects
+    let (reader, writer) = tokio::io::duplex(DOWNLOAD_BUFFER_SIZE);
+    let path = query.path.clone();
+    tokio::spawn(async move {
+        let result = client.get_to_writer(path.clone(), writer, CancellationToken::new());
+        if let Err(e) = result.await {
+            warn!("Download of {} failed: {:#}", path, e);
+        }
+    });
+
+    let name = file
+        .path
+        .file_name()
+        .map(|name| name.to_string_lossy().replace('"', ""))
+        .unwrap_or_default();
+    let mut response = Response::builder()
+        .header(header::CONTENT_TYPE, "application/octet-stream")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", name),
+        );
+    if let Some(size) = file.size {
+        response = response.header(header::CONTENT_LENGTH, size);
+    }
+    let body = Body::from_stream(ReaderStream::new(reader));
+    response
+        .body(body)
+        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e))
+}
+
+/// `PUT /upload`: streams the request body into a file
+async fn upload(
+    State(state): State<AppState>,
+    Query(query): Query<FileQuery>,
+    headers: axum::http::HeaderMap,
+    body: Body,
+) -> Result<Json<FileTransferOperationResult>, ApiError> {
+    let client = state.client(&query.session_key()).await?;
+    let size_hint = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|length| length.to_str().ok())
+        .and_then(|length| length.parse().ok());
+    let stream = body.into_data_stream().map_err(std::io::Error::other);
+    let reader = StreamReader::new(stream);
+    let result = client
+        .put_from_reader(reader, query.path, size_hint, CancellationToken::new())
+        .await?;
+    Ok(Json(result))
+}
+
+/// `GET /jobs`: status of every transfer job
+async fn list_jobs(State(state): State<AppState>) -> Json<Vec<JobStatus>> {
+    Json(state.jobs.list())
+}
+
+/// `POST /jobs`: starts a transfer job
+async fn submit_job(
+    State(state): State<AppState>,
+    Json(request): Json<TransferRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    state.access.check(&request.source.session_key)?;
+    state.access.check(&request.destination.session_key)?;
+    let status = state.jobs.submit(state.pool.clone(), request);
+    Ok((StatusCode::ACCEPTED, Json(status)))
+}
+
+/// `GET /jobs/{id}`: status and progress of a transfer job
+async fn job(
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<Json<JobStatus>, ApiError> {
+    state
+        .jobs
+        .get(id)
+        .map(Json)
+        .ok_or_else(|| job_not_found(id))
+}
+
+/// `DELETE /jobs/{id}`: cancels a transfer job
+async fn cancel_job(
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<(StatusCode, Json<JobStatus>), ApiError> {
+    let status = state.jobs.cancel(id).ok_or_else(|| job_not_found(id))?;
+    Ok((StatusCode::ACCEPTED, Json(status)))
+}
+
+/// `GET /jobs/{id}/events`: Server-Sent Events stream of a transfer job
+///
+/// Sends a `progress` event with a `FileTransferProgress` for every update
+/// reported by the transfer, then a single final event before closing: `result`
+/// with the `FileTransferOperationResult` of a completed or cancelled job, or
+/// `error` with the message of a failed job. Updates arriving faster than the
+/// client reads them are coalesced.
+async fn job_events(
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, ApiError> {
+    let status = state.jobs.subscribe(id).ok_or_else(|| job_not_found(id))?;
+    let events = stream::unfold(Some((status, true)), |next| async move {
+        let (mut status, mut first) = next?;
+        loop {
+            // The current status is sent right away, later ones once they change
+            if !first && status.changed().await.is_err() {
+                return None;
+            }
+            first = false;
+            let current = status.borrow_and_update().clone();
+            let event = match (current.state, &current.progress) {
+                (JobState::Running, Some(progress)) => {
+                    Event::default().event("progress").json_data(progress)
+                }
+                // Nothing transferred yet
+                (JobState::Running, None) => continue,
+                (JobState::Failed, _) => Event::default()
+                    .event("error")
+                    .json_data(json!({ "error": current.error })),
+                (JobState::Completed | JobState::Cancelled, _) => {
+                    Event::default().event("result").json_data(&current.result)
+                }
+            };
+            let next = (current.state == JobState::Running).then_some((status, false));
+            return Some((event, next));
+        }
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+fn job_not_found(id: u64) -> ApiError {
+    ApiError::new(
+        StatusCode::NOT_FOUND,
+        anyhow::anyhow!("no such job: {}", id),
+    )
+}
@@ -1,13 +1,11 @@
 use anyhow::anyhow;
-use openssh;
 use openssh_sftp_client::{Sftp, SftpOptions};
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Duration;
 use tracing::{debug, error, info, warn};
 
 use crate::client::SftpClient;
-use crate::types::SftpClientConfig;
+use crate::types::{ConnectOptions, KnownHostsPolicy, SftpClientConfig};
 use crate::utils::check_connection;
 
 /// Manager for SSH sessions that creates SFTP clients
@@ -45,13 +43,64 @@ impl SftpSessionManager {
         username: &str,
         control_dir: PathBuf,
         ssh_key_path: PathBuf,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let options = ConnectOptions {
+            ssh_key_path: Some(ssh_key_path),
+            control_dir: Some(control_dir),
+            known_hosts: KnownHostsPolicy::Accept,
+            ..ConnectOptions::default()
+        };
+        Self::connect_with_options(host, username, options).await
+    }
+
+    /// Establishes a new SSH connection to the remote host with custom options
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - The hostname or IP address of the remote server
+    /// * `username` - The username for SSH authentication
+    /// * `options` - Port, key, control directory, host key checking and timeout
+    ///
+    /// # Returns
+    ///
+    /// Returns a new `SftpSessionManager` with an active SSH session
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let manager = SftpSessionManager::connect_with_options(
+    ///     "example.com",
+    ///     "user",
+    ///     ConnectOptions {
+    ///         port: Some(2222),
+    ///         known_hosts: KnownHostsPolicy::Strict,
+    ///         ..ConnectOptions::default()
+    ///     },
+    /// ).await?;
+    /// ```
+    pub async fn connect_with_options(
+        host: &str,
+        username: &str,
+        options: ConnectOptions,
     ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         info!("Connecting to {:?}:{:?}", username, host);
-        let session = openssh::SessionBuilder::default()
+        let control_dir = options.control_dir.unwrap_or_else(std::env::temp_dir);
+        let mut builder = openssh::SessionBuilder::default();
+        builder
             .control_directory(&control_dir)
-            .keyfile(&ssh_key_path)
-            .known_hosts_check(openssh::KnownHosts::Accept)
-            .connect_timeout(Duration::from_secs(60))
+            .known_hosts_check(match options.known_hosts {
+                KnownHostsPolicy::Strict => openssh::KnownHosts::Strict,
+                KnownHostsPolicy::Add => openssh::KnownHosts::Add,
+                KnownHostsPolicy::Accept => openssh::KnownHosts::Accept,
+            })
+            .connect_timeout(options.connect_timeout);
+        if let Some(port) = options.port {
+            builder.port(port);
+        }
+        if let Some(ssh_key_path) = &options.ssh_key_path {
+            builder.keyfile(ssh_key_path);
+        }
+        let session = builder
             .connect(format!("ssh://{}@{}", username, host))
             .await?;
         Ok(Self {
@@ -82,6 +131,7 @@ impl SftpSessionManager {
     /// let client = manager.create_sftp_client(SftpClientConfig {
     ///     io_size: 32_768,
     ///     concurrency: 8,
+    ///     ..SftpClientConfig::default()
     /// }).await?;
     /// ```
     pub async fn create_sftp_client(
@@ -105,7 +155,7 @@ impl SftpSessionManager {
         )
         .await?;
         debug!("sftp client created successfully");
-        Ok(SftpClient::new(sftp, config))
+        Ok(SftpClient::new(sftp, session.clone(), config))
     }
 
     /// Closes the SSH session if no SFTP clients are using it
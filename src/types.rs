@@ -1,54 +1,171 @@
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::mpsc;
+
+use crate::filter::FileFilter;
 
 /// Metadata information for a file
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileMetadata {
     pub path: PathBuf,
     pub size: Option<u64>,
     pub file_type: FileType,
     pub last_accessed_at: Option<std::time::SystemTime>,
     pub last_modified_at: Option<std::time::SystemTime>,
+    /// Permission bits, e.g. `0o644` (including setuid, setgid and sticky bits)
+    pub permissions: Option<u32>,
+    /// Numeric owner user id
+    pub uid: Option<u32>,
+    /// Numeric owner group id
+    pub gid: Option<u32>,
+    /// Target of a symbolic link, as stored in the link (only set for symlinks)
+    pub link_target: Option<PathBuf>,
 }
 
-/// Type of file (regular file or directory)
-#[derive(Debug, Clone)]
+/// Type of file (regular file, directory or symbolic link)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum FileType {
     Regular,
     Directory,
+    Symlink,
 }
 
 /// Configuration for SFTP client operations
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SftpClientConfig {
     /// Buffer size for read/write operations in bytes
     pub io_size: usize,
     /// Number of concurrent operations allowed
     pub concurrency: usize,
+    /// Check the free space of the destination filesystem before `put` and
+    /// fail fast when the file does not fit (requires the
+    /// `statvfs@openssh.com` extension on the server)
+    pub check_disk_space: bool,
+    /// Largest file in bytes accepted by `read_to_bytes` and `read_to_string`
+    pub max_in_memory_size: u64,
 }
 
-impl SftpClientConfig {
+impl Default for SftpClientConfig {
     /// Creates a new configuration with default values
     /// - io_size: 65536 (64KB)
     /// - concurrency: 8
-    pub fn default() -> Self {
+    /// - check_disk_space: false
+    /// - max_in_memory_size: 16777216 (16MB)
+    fn default() -> Self {
         Self {
             io_size: 65536,
             concurrency: 8,
+            check_disk_space: false,
+            max_in_memory_size: 16 * 1024 * 1024,
         }
     }
+}
 
+impl SftpClientConfig {
     /// Creates a new configuration with custom values
     pub fn new(io_size: usize, concurrency: usize) -> Self {
         Self {
             io_size,
             concurrency,
+            ..Self::default()
+        }
+    }
+}
+
+/// How the SSH host key of the server is checked against the known hosts file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KnownHostsPolicy {
+    /// Reject hosts which are unknown or whose key changed (`StrictHostKeyChecking=yes`)
+    Strict,
+    /// Add unknown hosts, reject changed keys (`StrictHostKeyChecking=accept-new`)
+    #[default]
+    Add,
+    /// Accept any host key (`StrictHostKeyChecking=no`)
+    Accept,
+}
+
+/// Options for `SftpSessionManager::connect_with_options`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConnectOptions {
+    /// SSH port (defaults to the ssh configuration, usually 22)
+    pub port: Option<u16>,
+    /// Path to the private SSH key file (defaults to the ssh agent and default keys)
+    pub ssh_key_path: Option<PathBuf>,
+    /// Directory for SSH control sockets (defaults to the system temporary directory)
+    pub control_dir: Option<PathBuf>,
+    /// Host key checking policy
+    pub known_hosts: KnownHostsPolicy,
+    /// Maximum time to wait for the connection to be established
+    pub connect_timeout: Duration,
+}
+
+impl Default for ConnectOptions {
+    /// Creates options using the ssh defaults, `KnownHostsPolicy::Add` and a 60 seconds timeout
+    fn default() -> Self {
+        Self {
+            port: None,
+            ssh_key_path: None,
+            control_dir: None,
+            known_hosts: KnownHostsPolicy::default(),
+            connect_timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Server and account a pooled session is connected to
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SessionKey {
+    /// Hostname or IP address of the remote server
+    pub host: String,
+    /// SSH port (defaults to the SSH configuration of the host, usually 22)
+    pub port: Option<u16>,
+    /// Username for SSH authentication
+    pub user: String,
+}
+
+/// Configuration of a `SessionPool`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionPoolConfig {
+    /// Options used to connect every session; the port of the `SessionKey`
+    /// takes precedence over `connect_options.port`
+    pub connect_options: ConnectOptions,
+    /// Configuration of the client created for each session
+    pub client_config: SftpClientConfig,
+    /// Maximum number of sessions opened to the same key; once reached,
+    /// callers share the least busy session
+    pub max_sessions_per_key: usize,
+    /// Sessions unused for this long are closed
+    pub idle_timeout: Duration,
+}
+
+impl Default for SessionPoolConfig {
+    /// Creates a new configuration with default values
+    /// - connect_options: `ConnectOptions::default()`
+    /// - client_config: `SftpClientConfig::default()`
+    /// - max_sessions_per_key: 4
+    /// - idle_timeout: 300 seconds
+    fn default() -> Self {
+        Self {
+            connect_options: ConnectOptions::default(),
+            client_config: SftpClientConfig::default(),
+            max_sessions_per_key: 4,
+            idle_timeout: Duration::from_secs(300),
         }
     }
 }
 
 /// Result of a file transfer operation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
 pub enum FileTransferOperationResult {
     /// Transfer completed successfully
     Completed(FileTransferProgress),
@@ -59,7 +176,7 @@ pub enum FileTransferOperationResult {
 }
 
 /// Progress information for an ongoing or completed file transfer
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileTransferProgress {
     /// Source file path
     pub src_file: String,
@@ -67,10 +184,319 @@ pub struct FileTransferProgress {
     pub dest_file: String,
     /// Total size of the file in bytes
     pub file_size: u64,
+    /// Number of bytes of the file already present at the destination
+    pub bytes_transferred: u64,
     /// Percentage of transfer completed (0.0 to 100.0)
     pub percentage_progress: f64,
 }
 
+/// Direction of a directory sync
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncDirection {
+    /// Mirror the local directory onto the remote server
+    Upload,
+    /// Mirror the remote directory onto local storage
+    Download,
+}
+
+/// Options controlling a directory sync
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncOptions {
+    /// Delete files and directories on the destination which do not exist on the source
+    pub delete: bool,
+    /// Only compute the planned actions without transferring or deleting anything
+    pub dry_run: bool,
+    /// Compare file contents using SHA-256 checksums instead of modification times
+    pub checksum: bool,
+    /// Only sync entries accepted by this filter
+    #[serde(skip)]
+    pub filter: Option<FileFilter>,
+}
+
+/// Reason why a file is transferred during a sync
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncReason {
+    /// The file does not exist on the destination
+    Missing,
+    /// The file size differs between source and destination
+    SizeChanged,
+    /// The file modification time differs between source and destination
+    ModifiedTimeChanged,
+    /// The file checksum differs between source and destination
+    ChecksumChanged,
+}
+
+/// Single action planned or performed by a directory sync
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum SyncAction {
+    /// Create a directory missing on the destination
+    CreateDir { path: String },
+    /// Transfer a new or changed file from source to destination
+    Transfer {
+        src_file: String,
+        dest_file: String,
+        reason: SyncReason,
+    },
+    /// Delete an extraneous entry from the destination
+    Delete { path: String, file_type: FileType },
+}
+
+impl std::fmt::Display for SyncAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SyncAction::CreateDir { path } => write!(f, "mkdir {}", path),
+            SyncAction::Transfer {
+                src_file,
+                dest_file,
+                reason,
+            } => {
+                let reason = match reason {
+                    SyncReason::Missing => "new",
+                    SyncReason::SizeChanged => "size changed",
+                    SyncReason::ModifiedTimeChanged => "mtime changed",
+                    SyncReason::ChecksumChanged => "checksum changed",
+                };
+                write!(f, "copy {} -> {} ({})", src_file, dest_file, reason)
+            }
+            SyncAction::Delete { path, .. } => write!(f, "delete {}", path),
+        }
+    }
+}
+
+/// Result of a directory sync
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncReport {
+    /// Actions in the order they were (or, for a dry run, would be) performed
+    pub actions: Vec<SyncAction>,
+    /// Whether this was a dry run and nothing was modified
+    pub dry_run: bool,
+    /// Whether the sync was cancelled before all actions were performed
+    pub cancelled: bool,
+}
+
+/// Options controlling a recursive remote tree walk
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WalkOptions {
+    /// Maximum depth to descend to, where direct children of the root have depth 1
+    pub max_depth: Option<usize>,
+    /// Follow symbolic links, reporting the metadata of their targets and
+    /// descending into linked directories (each directory is visited once)
+    pub follow_symlinks: bool,
+    /// Sort the entries of each directory by file name
+    pub sort: bool,
+    /// Number of directories read concurrently (defaults to the client concurrency)
+    pub concurrency: Option<usize>,
+    /// Only yield entries accepted by this filter; excluded directories are not descended into
+    #[serde(skip)]
+    pub filter: Option<FileFilter>,
+}
+
+/// Entry yielded by a remote tree walk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalkEntry {
+    /// Metadata of the entry
+    pub metadata: FileMetadata,
+    /// Depth of the entry below the walk root (direct children have depth 1)
+    pub depth: usize,
+}
+
+/// Disk usage summary of a remote directory tree
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DiskUsage {
+    /// Total size of all regular files in bytes
+    pub total_bytes: u64,
+    /// Number of regular files
+    pub file_count: u64,
+    /// Number of directories, excluding the root
+    pub dir_count: u64,
+    /// Usage of each direct sub-directory, keyed by its path (only when requested)
+    pub subdirectories: Option<BTreeMap<PathBuf, DiskUsage>>,
+}
+
+impl DiskUsage {
+    /// Counts one entry: its size and a file for regular files, a directory
+    /// for directories, nothing for symbolic links
+    pub(crate) fn add(&mut self, file_type: FileType, size: Option<u64>) {
+        match file_type {
+            FileType::Regular => {
+                self.total_bytes += size.unwrap_or(0);
+                self.file_count += 1;
+            }
+            FileType::Directory => self.dir_count += 1,
+            FileType::Symlink => {}
+        }
+    }
+}
+
+/// Capacity of a remote filesystem as reported by `statvfs@openssh.com`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskSpace {
+    /// Fundamental block size in bytes
+    pub block_size: u64,
+    /// Total size of the filesystem in bytes
+    pub total_bytes: u64,
+    /// Free space in bytes, including space reserved for the superuser
+    pub free_bytes: u64,
+    /// Free space in bytes available to the connected user
+    pub available_bytes: u64,
+    /// Total number of inodes
+    pub total_inodes: u64,
+    /// Number of free inodes
+    pub free_inodes: u64,
+    /// Number of free inodes available to the connected user
+    pub available_inodes: u64,
+    /// Maximum length of a file name
+    pub max_name_length: u64,
+}
+
+/// Attributes to change with `SftpClient::set_metadata`; `None` fields are left unchanged
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FileAttributes {
+    /// Permission bits, e.g. `0o640` (including setuid, setgid and sticky bits)
+    pub permissions: Option<u32>,
+    /// Numeric owner user id
+    pub uid: Option<u32>,
+    /// Numeric owner group id
+    pub gid: Option<u32>,
+    /// File size in bytes; the file is truncated or extended with zeros
+    pub size: Option<u64>,
+    /// Last access time (second precision)
+    pub accessed_at: Option<SystemTime>,
+    /// Last modification time (second precision)
+    pub modified_at: Option<SystemTime>,
+}
+
+/// Options for opening a remote file with `SftpClient::open`
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OpenOptions {
+    /// Open the file for reading
+    pub read: bool,
+    /// Open the file for writing
+    pub write: bool,
+    /// Open the file for writing, positioned at the end of the file
+    pub append: bool,
+    /// Create the file if it does not exist
+    pub create: bool,
+    /// Truncate the file to zero length when opening it
+    pub truncate: bool,
+    /// Create the file, failing if it already exists
+    pub exclusive: bool,
+}
+
+/// Options for following a growing remote file with `SftpClient::tail`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TailOptions {
+    /// Interval between two checks for new data once the end of the file is reached
+    pub poll_interval: Duration,
+    /// Stream the existing contents of the file before following it, instead
+    /// of only the data appended after the call
+    pub from_start: bool,
+}
+
+impl Default for TailOptions {
+    /// Creates options polling every second from the current end of the file
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(1),
+            from_start: false,
+        }
+    }
+}
+
+/// Event yielded while following a remote file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum TailEvent {
+    /// Bytes appended to the file
+    Data(Bytes),
+    /// The file shrank below the current position; following resumes from its start
+    Truncated,
+    /// The path now refers to a new file (e.g. after log rotation); following
+    /// resumes from the start of the new file
+    Rotated,
+}
+
+/// Options for transferring a file between two servers with `SftpClient::transfer_to`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransferOptions {
+    /// Continue a previous partial transfer by appending to the destination
+    /// file when it is smaller than the source, instead of starting over
+    pub resume: bool,
+    /// Compare the SHA-256 checksums of the source and destination files once
+    /// the transfer completes
+    pub verify_checksum: bool,
+    /// Channel receiving a `FileTransferProgress` update after every chunk.
+    /// Updates are dropped rather than slowing the transfer down when the
+    /// channel is full.
+    #[serde(skip)]
+    pub progress: Option<mpsc::Sender<FileTransferProgress>>,
+}
+
+/// Errors which callers may want to handle specifically
+///
+/// Operations return `anyhow::Error`; these errors can be recovered with
+/// `err.downcast_ref::<SftpError>()`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SftpError {
+    /// The operation was cancelled through its `CancellationToken`
+    Cancelled,
+    /// The remote path does not exist
+    NotFound { path: String },
+    /// The destination filesystem does not have enough free space
+    InsufficientSpace { required: u64, available: u64 },
+    /// The file is larger than `max_in_memory_size`
+    FileTooLarge { size: u64, limit: u64 },
+    /// The destination of a transfer does not have the checksum of its source
+    ChecksumMismatch { src_file: String, dest_file: String },
+    /// The SSH connection or the SFTP subsystem could not be set up
+    ConnectionFailed { host: String, reason: String },
+}
+
+impl std::fmt::Display for SftpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SftpError::Cancelled => write!(f, "operation cancelled by user"),
+            SftpError::NotFound { path } => write!(f, "no such file or directory: {}", path),
+            SftpError::InsufficientSpace {
+                required,
+                available,
+            } => write!(
+                f,
+                "insufficient disk space: {} bytes required, {} bytes available",
+                required, available
+            ),
+            SftpError::FileTooLarge { size, limit } => write!(
+                f,
+                "file too large to read into memory: {} bytes, limit is {} bytes",
+                size, limit
+            ),
+            SftpError::ChecksumMismatch {
+                src_file,
+                dest_file,
+            } => write!(
+                f,
+                "checksum mismatch between {} and {}",
+                src_file, dest_file
+            ),
+            SftpError::ConnectionFailed { host, reason } => {
+                write!(f, "failed to connect to {}: {}", host, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SftpError {}
+
 /// Internal configuration wrapper with Arc for shared ownership
 #[derive(Debug, Clone)]
 pub(crate) struct SftpClientConfigArc {
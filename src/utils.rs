@@ -1,4 +1,12 @@
+use bytes::BytesMut;
+use openssh_sftp_client::metadata::{MetaData, Permissions};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
 use std::time::Duration;
+use tokio::io::AsyncReadExt;
+use tokio::sync::mpsc;
+
+use crate::types::{FileMetadata, FileTransferProgress, FileType};
 
 /// Routine to check the underlying SSH connection is active or not for the SFTP client.
 /// This function runs in a loop, checking the connection every 10 seconds.
@@ -17,3 +25,108 @@ pub fn check_connection<'session>(
         Ok(())
     })
 }
+
+/// Computes the SHA-256 checksum of a local file.
+pub(crate) async fn local_sha256(path: &str, io_size: usize) -> Result<Vec<u8>, anyhow::Error> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; io_size];
+    loop {
+        let n = file.read(&mut buffer[..]).await?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(hasher.finalize().to_vec())
+}
+
+/// Computes the SHA-256 checksum of a remote file by streaming it through the client.
+pub(crate) async fn remote_sha256(
+    sftp: &openssh_sftp_client::Sftp,
+    path: &str,
+    io_size: usize,
+) -> Result<Vec<u8>, anyhow::Error> {
+    let mut file = sftp.open(path).await?;
+    let mut hasher = Sha256::new();
+    while let Some(buf) = file
+        .read(io_size as u32, BytesMut::with_capacity(io_size))
+        .await?
+    {
+        hasher.update(&buf);
+    }
+    Ok(hasher.finalize().to_vec())
+}
+
+/// Converts SFTP attributes into the `FileMetadata` of `path`.
+pub(crate) fn file_metadata(path: PathBuf, metadata: &MetaData) -> FileMetadata {
+    let file_type = metadata.file_type();
+    FileMetadata {
+        path,
+        size: metadata.len(),
+        file_type: if file_type.is_some_and(|t| t.is_dir()) {
+            FileType::Directory
+        } else if file_type.is_some_and(|t| t.is_symlink()) {
+            FileType::Symlink
+        } else {
+            FileType::Regular
+        },
+        last_accessed_at: metadata.accessed().map(|t| t.as_system_time()),
+        last_modified_at: metadata.modified().map(|t| t.as_system_time()),
+        permissions: metadata.permissions().map(mode),
+        uid: metadata.uid(),
+        gid: metadata.gid(),
+        link_target: None,
+    }
+}
+
+/// Converts SFTP permissions into numeric mode bits
+pub(crate) fn mode(permissions: Permissions) -> u32 {
+    [
+        (permissions.suid(), 0o4000),
+        (permissions.sgid(), 0o2000),
+        (permissions.svtx(), 0o1000),
+        (permissions.read_by_owner(), 0o400),
+        (permissions.write_by_owner(), 0o200),
+        (permissions.execute_by_owner(), 0o100),
+        (permissions.read_by_group(), 0o40),
+        (permissions.write_by_group(), 0o20),
+        (permissions.execute_by_group(), 0o10),
+        (permissions.read_by_other(), 0o4),
+        (permissions.write_by_other(), 0o2),
+        (permissions.execute_by_other(), 0o1),
+    ]
+    .iter()
+    .filter(|(set, _)| *set)
+    .fold(0, |mode, (_, bit)| mode | bit)
+}
+
+/// Sends the progress of a transfer to an optional channel without blocking
+pub(crate) struct ProgressReporter {
+    pub(crate) sender: Option<mpsc::Sender<FileTransferProgress>>,
+    pub(crate) src_file: String,
+    pub(crate) dest_file: String,
+    pub(crate) file_size: u64,
+}
+
+impl ProgressReporter {
+    /// Reports that `bytes_transferred` bytes of the file reached the destination.
+    /// The update is dropped if the channel is full or closed.
+    pub(crate) fn report(&self, bytes_transferred: u64) {
+        let Some(sender) = &self.sender else {
+            return;
+        };
+        let percentage_progress = if self.file_size == 0 {
+            100.0_f64
+        } else {
+            bytes_transferred as f64 * 100.0 / self.file_size as f64
+        };
+        let _ = sender.try_send(FileTransferProgress {
+            src_file: self.src_file.clone(),
+            dest_file: self.dest_file.clone(),
+            file_size: self.file_size,
+            bytes_transferred,
+            percentage_progress,
+        });
+    }
+}